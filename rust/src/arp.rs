@@ -0,0 +1,374 @@
+//! Tick-scheduled arpeggiator.
+//!
+//! Unlike `Loop`/`Track`, which replay a fixed set of pre-baked events, the
+//! arpeggiator takes a held chord and generates its pattern on the fly,
+//! scheduling each note-on/note-off pair onto future clock ticks as the
+//! chord is held. It exposes the same `tick(clock_count) -> Vec<Vec<u8>>`
+//! shape as `SequencePlayer::tick`, so it can be driven from the same
+//! clock callback.
+
+use std::collections::BTreeMap;
+
+/// Order the arpeggiator steps through the held notes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArpPattern {
+    Up,
+    Down,
+    UpDown,
+    Random,
+}
+
+/// A tick-scheduled arpeggiator.
+///
+/// `rate_ticks` and `gate_ticks` are both expressed in MIDI clock ticks
+/// (24 ppqn), e.g. `6` = a sixteenth note. `rate_ticks` is the spacing
+/// between successive steps; `gate_ticks` (capped to `rate_ticks`) is how
+/// long each step's note stays on before its note-off, so a gate shorter
+/// than the rate leaves a gap between notes instead of legato.
+pub struct Arpeggiator {
+    pattern: ArpPattern,
+    channel: u8,
+    rate_ticks: u64,
+    gate_ticks: u64,
+    held_notes: Vec<u8>,
+    step_index: usize,
+    /// Clock at which the next step's note-on should fire. `None` while no
+    /// chord is held.
+    next_step_clock: Option<u64>,
+    /// Every scheduled note-on/note-off, keyed by the `clock_count` it
+    /// should fire on. A `BTreeMap` rather than a plain min-heap since
+    /// releasing the chord needs to find and drop not-yet-fired entries by
+    /// key, not just pop them in order.
+    scheduled: BTreeMap<u64, Vec<Vec<u8>>>,
+    /// xorshift64 state for the `Random` pattern. Self-contained rather
+    /// than pulling in a crate for the sake of picking one note out of a
+    /// held chord.
+    rng_state: u64,
+}
+
+impl Arpeggiator {
+    pub fn new(pattern: ArpPattern, channel: u8, rate_ticks: u64, gate_ticks: u64) -> Self {
+        let rate_ticks = rate_ticks.max(1);
+        Self {
+            pattern,
+            channel: channel & 0x0F,
+            rate_ticks,
+            gate_ticks: gate_ticks.clamp(1, rate_ticks),
+            held_notes: Vec::new(),
+            step_index: 0,
+            next_step_clock: None,
+            scheduled: BTreeMap::new(),
+            rng_state: 0x2545_F491_4F6C_DD1D,
+        }
+    }
+
+    pub fn set_pattern(&mut self, pattern: ArpPattern) {
+        self.pattern = pattern;
+        self.step_index = 0;
+    }
+
+    pub fn set_rate_and_gate(&mut self, rate_ticks: u64, gate_ticks: u64) {
+        self.rate_ticks = rate_ticks.max(1);
+        self.gate_ticks = gate_ticks.clamp(1, self.rate_ticks);
+    }
+
+    /// Replace the held chord at `clock_count`. An empty `notes` releases
+    /// the chord: a note already sounding still turns off on its scheduled
+    /// note-off tick, and any note-on scheduled but not yet fired is dropped
+    /// so it never sounds with nothing left holding it down.
+    pub fn set_held_notes(&mut self, notes: Vec<u8>, clock_count: u64) {
+        if notes.is_empty() {
+            self.release(clock_count);
+            self.held_notes.clear();
+            self.next_step_clock = None;
+            return;
+        }
+
+        self.held_notes = notes;
+        self.step_index = 0;
+        if self.next_step_clock.is_none() {
+            self.next_step_clock = Some(clock_count);
+        }
+    }
+
+    /// Add `note` to the held chord (no-op if already held). Mirrors
+    /// `set_held_notes` pushing one note at a time, so a player feeding in
+    /// individual incoming Note-On/Note-Off messages doesn't have to track
+    /// the held set itself.
+    pub fn note_on(&mut self, note: u8, clock_count: u64) {
+        if self.held_notes.contains(&note) {
+            return;
+        }
+        let mut notes = self.held_notes.clone();
+        notes.push(note);
+        self.set_held_notes(notes, clock_count);
+    }
+
+    /// Remove `note` from the held chord. Releasing the last held note
+    /// behaves exactly like `set_held_notes(vec![], ..)`.
+    pub fn note_off(&mut self, note: u8, clock_count: u64) {
+        let notes: Vec<u8> = self
+            .held_notes
+            .iter()
+            .copied()
+            .filter(|&n| n != note)
+            .collect();
+        self.set_held_notes(notes, clock_count);
+    }
+
+    fn release(&mut self, clock_count: u64) {
+        let mut still_sounding = Vec::new();
+        self.scheduled.retain(|&tick, events| {
+            events.retain(|event| {
+                let is_future_note_on = event
+                    .first()
+                    .map(|status| status & 0xF0 == 0x90)
+                    .unwrap_or(false)
+                    && tick >= clock_count;
+                if is_future_note_on {
+                    // Hasn't fired yet and never will now: drop it rather
+                    // than letting it trigger a note with no chord behind
+                    // it, and don't bother pairing a note-off for a note
+                    // that'll never turn on.
+                    false
+                } else {
+                    if event.first().map(|s| s & 0xF0 == 0x90).unwrap_or(false) && tick < clock_count {
+                        still_sounding.push(event[1]);
+                    }
+                    true
+                }
+            });
+            !events.is_empty()
+        });
+
+        if !still_sounding.is_empty() {
+            let note_offs = still_sounding
+                .into_iter()
+                .map(|note| vec![0x80 | self.channel, note, 0])
+                .collect::<Vec<_>>();
+            self.scheduled.entry(clock_count).or_default().extend(note_offs);
+        }
+    }
+
+    /// Advance by one tick: schedule the next step if it's due, then return
+    /// (and clear) every event scheduled for exactly `clock_count`.
+    pub fn tick(&mut self, clock_count: u64) -> Vec<Vec<u8>> {
+        if let Some(next) = self.next_step_clock {
+            if clock_count >= next && !self.held_notes.is_empty() {
+                self.schedule_step(clock_count);
+                self.next_step_clock = Some(clock_count + self.rate_ticks);
+            }
+        }
+        self.scheduled.remove(&clock_count).unwrap_or_default()
+    }
+
+    /// Schedule one step's note-on (at `clock_count`) and its matching
+    /// note-off (`gate_ticks` later). These are always inserted together --
+    /// the critical invariant that keeps a pattern change or chord release
+    /// from ever leaving a note stuck on.
+    fn schedule_step(&mut self, clock_count: u64) {
+        let note = self.next_note();
+        let note_on_tick = clock_count;
+        let note_off_tick = note_on_tick + self.gate_ticks;
+
+        self.scheduled
+            .entry(note_on_tick)
+            .or_default()
+            .push(vec![0x90 | self.channel, note, 100]);
+        self.scheduled
+            .entry(note_off_tick)
+            .or_default()
+            .push(vec![0x80 | self.channel, note, 0]);
+    }
+
+    fn next_note(&mut self) -> u8 {
+        let n = self.held_notes.len();
+        debug_assert!(n > 0, "next_note called with no held notes");
+
+        match self.pattern {
+            ArpPattern::Up => {
+                let note = self.held_notes[self.step_index % n];
+                self.step_index += 1;
+                note
+            }
+            ArpPattern::Down => {
+                let note = self.held_notes[n - 1 - (self.step_index % n)];
+                self.step_index += 1;
+                note
+            }
+            ArpPattern::UpDown => {
+                if n == 1 {
+                    return self.held_notes[0];
+                }
+                // Bounces 0..n-1..0 without repeating the end notes:
+                // cycle length 2*(n-1), folding the back half of the cycle
+                // back onto the ascending index.
+                let cycle_len = 2 * (n - 1);
+                let pos = self.step_index % cycle_len;
+                let idx = if pos < n { pos } else { cycle_len - pos };
+                self.step_index += 1;
+                self.held_notes[idx]
+            }
+            ArpPattern::Random => {
+                let idx = (self.next_rand() as usize) % n;
+                self.held_notes[idx]
+            }
+        }
+    }
+
+    fn next_rand(&mut self) -> u64 {
+        let mut x = self.rng_state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.rng_state = x;
+        x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_step_pairs_note_on_and_off() {
+        let mut arp = Arpeggiator::new(ArpPattern::Up, 0, 6, 3);
+        arp.set_held_notes(vec![60], 0);
+
+        // The note-on fires on the tick `set_held_notes` was called on.
+        let events = arp.tick(0);
+        assert_eq!(events, vec![vec![0x90, 60, 100]]);
+
+        // Nothing fires in between...
+        assert!(arp.tick(1).is_empty());
+        assert!(arp.tick(2).is_empty());
+
+        // ...and the matching note-off fires exactly `gate_ticks` later.
+        let events = arp.tick(3);
+        assert_eq!(events, vec![vec![0x80, 60, 0]]);
+    }
+
+    #[test]
+    fn test_release_lets_inflight_note_off_fire_and_stops_future_steps() {
+        let mut arp = Arpeggiator::new(ArpPattern::Up, 0, 6, 3);
+        arp.set_held_notes(vec![60], 0);
+        // Fires the first step's note-on; its matching note-off is queued
+        // for tick 3, and the next step's note-on would be due at tick 6.
+        arp.tick(0);
+
+        // Release mid-gate, before either is due.
+        arp.set_held_notes(vec![], 1);
+        assert!(arp.tick(1).is_empty());
+
+        // The in-flight note still turns off on schedule rather than
+        // hanging forever...
+        let events = arp.tick(3);
+        assert_eq!(events, vec![vec![0x80, 60, 0]]);
+
+        // ...but the next step never fires now that the chord is released.
+        assert!(arp.tick(6).is_empty());
+    }
+
+    #[test]
+    fn test_changing_pattern_never_leaves_a_note_stuck_on() {
+        // Every scheduled note-on must have a paired note-off queued at the
+        // same time -- the invariant `schedule_step` documents -- even
+        // across a pattern change and release mid-held-chord. Drive one
+        // continuous sweep of ticks (rather than skipping ahead) so every
+        // queued note-off actually gets drained and counted.
+        let mut arp = Arpeggiator::new(ArpPattern::Up, 0, 6, 3);
+        arp.set_held_notes(vec![60, 64, 67], 0);
+
+        let mut all_note_ons = 0;
+        let mut all_note_offs = 0;
+        for tick in 0..=24u64 {
+            match tick {
+                6 => arp.set_pattern(ArpPattern::Down),
+                12 => arp.set_pattern(ArpPattern::UpDown),
+                18 => arp.set_held_notes(vec![], tick),
+                _ => {}
+            }
+            for event in arp.tick(tick) {
+                if event[0] & 0xF0 == 0x90 {
+                    all_note_ons += 1;
+                } else if event[0] & 0xF0 == 0x80 {
+                    all_note_offs += 1;
+                }
+            }
+        }
+        assert!(all_note_ons > 0);
+        assert_eq!(all_note_ons, all_note_offs);
+    }
+
+    #[test]
+    fn test_up_pattern_steps_through_notes_in_order() {
+        let mut arp = Arpeggiator::new(ArpPattern::Up, 0, 6, 3);
+        arp.set_held_notes(vec![60, 64, 67], 0);
+
+        let mut notes_on = Vec::new();
+        for tick in (0..18).step_by(6) {
+            for event in arp.tick(tick) {
+                if event[0] & 0xF0 == 0x90 {
+                    notes_on.push(event[1]);
+                }
+            }
+        }
+        assert_eq!(notes_on, vec![60, 64, 67]);
+    }
+
+    #[test]
+    fn test_down_pattern_steps_through_notes_in_reverse() {
+        let mut arp = Arpeggiator::new(ArpPattern::Down, 0, 6, 3);
+        arp.set_held_notes(vec![60, 64, 67], 0);
+
+        let mut notes_on = Vec::new();
+        for tick in (0..18).step_by(6) {
+            for event in arp.tick(tick) {
+                if event[0] & 0xF0 == 0x90 {
+                    notes_on.push(event[1]);
+                }
+            }
+        }
+        assert_eq!(notes_on, vec![67, 64, 60]);
+    }
+
+    #[test]
+    fn test_updown_pattern_bounces_without_repeating_ends() {
+        let mut arp = Arpeggiator::new(ArpPattern::UpDown, 0, 6, 3);
+        arp.set_held_notes(vec![60, 64, 67], 0);
+
+        let mut notes_on = Vec::new();
+        for tick in (0..24).step_by(6) {
+            for event in arp.tick(tick) {
+                if event[0] & 0xF0 == 0x90 {
+                    notes_on.push(event[1]);
+                }
+            }
+        }
+        // Cycle length is 2*(n-1) = 4: 60, 64, 67, 64, then repeats.
+        assert_eq!(notes_on, vec![60, 64, 67, 64]);
+    }
+
+    #[test]
+    fn test_note_on_note_off_track_held_chord_incrementally() {
+        let mut arp = Arpeggiator::new(ArpPattern::Up, 0, 6, 3);
+        arp.note_on(60, 0);
+        arp.note_on(64, 0);
+
+        let events = arp.tick(0);
+        assert_eq!(events, vec![vec![0x90, 60, 100]]);
+
+        arp.note_off(60, 1);
+        arp.note_off(64, 1);
+        assert!(arp.tick(1).is_empty());
+
+        // Both notes released: the in-flight note still turns off on
+        // schedule (tick 3)...
+        let events = arp.tick(3);
+        assert_eq!(events, vec![vec![0x80, 60, 0]]);
+
+        // ...but the next step's note-on, which would have been due at
+        // tick 6, never fires now that the chord is empty.
+        assert!(arp.tick(6).is_empty());
+    }
+}
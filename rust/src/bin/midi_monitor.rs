@@ -48,11 +48,14 @@ fn main() {
     println!("{:<12} {:<20} {:<30} {}", "TIMESTAMP", "TYPE", "DATA (HEX)", "DETAILS");
     println!("{}", "-".repeat(80));
 
+    let args: Vec<String> = std::env::args().collect();
+    let time_signature = parse_time_signature_arg(&args).unwrap_or((4, 4));
+
     let _connection = midi_in.connect(
         port,
         "midi-monitor-in",
         move |timestamp, message, _| {
-            print_midi_message(timestamp, message);
+            print_midi_message(timestamp, message, time_signature);
         },
         (),
     );
@@ -60,7 +63,6 @@ fn main() {
     match _connection {
         Ok(conn) => {
             // Check for --duration argument, default to waiting for Enter
-            let args: Vec<String> = std::env::args().collect();
             let duration_secs: Option<u64> = args
                 .iter()
                 .position(|a| a == "--duration")
@@ -71,7 +73,7 @@ fn main() {
                 println!("\nMonitoring for {} seconds...\n", secs);
                 std::thread::sleep(std::time::Duration::from_secs(secs));
             } else {
-                println!("\nPress Enter to quit (or use --duration <secs>)...\n");
+                println!("\nPress Enter to quit (or use --duration <secs> / --time-signature N/D)...\n");
                 let mut input = String::new();
                 io::stdin().read_line(&mut input).unwrap();
             }
@@ -83,29 +85,65 @@ fn main() {
     }
 }
 
-fn print_midi_message(timestamp: u64, message: &[u8]) {
+/// Parse a `--time-signature N/D` argument (e.g. `6/8`), if present.
+fn parse_time_signature_arg(args: &[String]) -> Option<(u8, u8)> {
+    let value = args
+        .iter()
+        .position(|a| a == "--time-signature")
+        .and_then(|i| args.get(i + 1))?;
+    let (num, den) = value.split_once('/')?;
+    Some((num.trim().parse().ok()?, den.trim().parse().ok()?))
+}
+
+/// Number of MIDI clock pulses in one musical beat of a time signature with
+/// the given denominator (quarter = 24, eighth = 12, ...). Mirrors
+/// `midi::clocks_per_beat`; kept local since this binary has no lib to share
+/// code with the main app.
+fn clocks_per_beat(denominator: u8) -> u64 {
+    (24 * 4) / denominator.max(1) as u64
+}
+
+fn print_midi_message(timestamp: u64, message: &[u8], time_signature: (u8, u8)) {
     if message.is_empty() {
         return;
     }
 
     let hex_str: String = message.iter().map(|b| format!("{:02X} ", b)).collect();
 
-    let (msg_type, details) = parse_midi_message(message);
+    let (msg_type, details) = parse_midi_message(message, time_signature);
 
     // Flush to ensure immediate output
     println!("{:<12} {:<20} {:<30} {}", timestamp, msg_type, hex_str.trim(), details);
     io::stdout().flush().unwrap();
 }
 
-fn parse_midi_message(message: &[u8]) -> (&'static str, String) {
+fn parse_midi_message(message: &[u8], time_signature: (u8, u8)) -> (&'static str, String) {
     if message.is_empty() {
         return ("EMPTY", String::new());
     }
 
     let status = message[0];
+    let (beats_per_bar, denominator) = time_signature;
+    let clocks_per_beat = clocks_per_beat(denominator);
+    let clocks_per_bar = beats_per_bar as u64 * clocks_per_beat;
 
-    // Real-time messages (single byte, 0xF8-0xFF)
+    // System common (0xF2) and real-time messages (single byte, 0xF8-0xFF)
     match status {
+        0xF2 => {
+            if message.len() >= 3 {
+                // SPP counts 16th-note "MIDI beats"; 6 clocks per 16th note
+                // at 24 ppqn, regardless of the configured time signature.
+                let spp = ((message[2] as u16) << 7) | (message[1] as u16);
+                let clocks = spp as u64 * 6;
+                let bar = (clocks / clocks_per_bar) + 1;
+                let beat = ((clocks / clocks_per_beat) % beats_per_bar as u64) + 1;
+                return (
+                    "SONG_POSITION",
+                    format!("SPP:{} -> Bar {} Beat {}", spp, bar, beat),
+                );
+            }
+            return ("SONG_POSITION", String::new());
+        }
         0xF8 => return ("CLOCK", "MIDI Clock pulse (24 ppqn)".to_string()),
         0xFA => return ("START", "Start playback from beginning".to_string()),
         0xFB => return ("CONTINUE", "Continue playback".to_string()),
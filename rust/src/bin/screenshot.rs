@@ -1,12 +1,46 @@
-//! Screenshot trigger utility.
+//! Remote-control trigger utility.
 //!
-//! Sends MIDI CC 119 value 127 to trigger a screenshot in the looper app.
-//! Usage: cargo run --bin screenshot
+//! Sends a MIDI CC that fires one of the looper's remote-control actions
+//! (screenshot, table navigation/edits, transport). Usage:
+//!
+//!   cargo run --bin screenshot                  # trigger a screenshot (default)
+//!   cargo run --bin screenshot -- select-next   # advance the selected slot
+//!
+//! Mirrors `remote::RemoteControlConfig`'s CC mapping; kept local since this
+//! binary has no lib to share code with the main app.
 
 use midir::MidiOutput;
 
+/// CC number -> action name, matching `remote::RemoteControlConfig::default`.
+const CC_MAPPINGS: &[(u8, &str)] = &[
+    (119, "screenshot"),
+    (20, "select-prev"),
+    (21, "select-next"),
+    (22, "loop-prev"),
+    (23, "loop-next"),
+    (24, "quan-down"),
+    (25, "quan-up"),
+    (26, "next-prev"),
+    (27, "next-next"),
+    (28, "start"),
+    (29, "stop"),
+    (30, "record-arm"),
+    (31, "arp-arm"),
+    (32, "queue-all"),
+];
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let midi_out = MidiOutput::new("screenshot-trigger")?;
+    let action = std::env::args().nth(1).unwrap_or_else(|| "screenshot".to_string());
+    let Some(&(cc, _)) = CC_MAPPINGS.iter().find(|(_, name)| *name == action) else {
+        eprintln!("Unknown action: {}", action);
+        eprintln!(
+            "Available actions: {}",
+            CC_MAPPINGS.iter().map(|(_, n)| *n).collect::<Vec<_>>().join(", ")
+        );
+        return Ok(());
+    };
+
+    let midi_out = MidiOutput::new("remote-trigger")?;
     let ports = midi_out.ports();
 
     if ports.is_empty() {
@@ -28,13 +62,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let port = &ports[port_idx];
     let port_name = midi_out.port_name(port)?;
 
-    let mut conn = midi_out.connect(port, "screenshot-trigger")?;
+    let mut conn = midi_out.connect(port, "remote-trigger")?;
 
-    // Send CC 119 value 127 on channel 1 (status byte 0xB0)
+    // Send the mapped CC at full value on channel 1 (status byte 0xB0)
     // Format: [status, cc_number, value]
-    let cc_message = [0xB0, 119, 127];
+    let cc_message = [0xB0, cc, 127];
     conn.send(&cc_message)?;
 
-    println!("Screenshot trigger sent to: {}", port_name);
+    println!("'{}' (CC {}) sent to: {}", action, cc, port_name);
     Ok(())
 }
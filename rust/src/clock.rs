@@ -9,32 +9,162 @@
 //! [`ClockState`] is designed to be shared between a MIDI input thread
 //! and the GUI thread. All state is wrapped in atomic types or mutexes.
 
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::f64::consts::PI;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 
 use crate::midi::{
-    BEATS_PER_BAR, CLOCKS_PER_BEAT, MIDI_CLOCK, MIDI_CONTINUE, MIDI_START, MIDI_STOP,
+    clocks_per_beat, decode_spp, spp_to_clocks, BEATS_PER_BAR, CLOCKS_PER_BEAT, MIDI_CLOCK,
+    MIDI_CONTINUE, MIDI_SPP, MIDI_START, MIDI_STOP,
 };
 
-/// Size of the rolling window for BPM calculation (1 bar = 96 clocks at 24 ppqn in 4/4)
+/// Size of the rolling window for BPM calculation in 4/4 (1 bar = 96 clocks
+/// at 24 ppqn), used only as the default `ClockTimeBuffer` capacity before a
+/// time signature is known.
 const BPM_WINDOW_CLOCKS: usize = 96;
 
+/// Damping ratio for the tempo DLL. 0.707 (critically damped-ish) gives a
+/// quick settle with minimal overshoot; this is the value Ardour's MIDI
+/// clock slave uses and we have no reason to deviate from it.
+const DLL_ZETA: f64 = 0.707;
+
+/// Default loop bandwidth in Hz if `LooperConfig` doesn't override it.
+/// Higher bandwidth tracks tempo changes faster but is noisier; lower is
+/// smoother but slower to respond. 1.0 Hz is Ardour's default.
+pub const DEFAULT_DLL_BANDWIDTH_HZ: f64 = 1.0;
+
+/// Minimum dropout timeout, regardless of the estimated clock period. Guards
+/// against a too-aggressive timeout at very high BPM or before tempo has
+/// been established.
+const MIN_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// How many expected clock intervals may elapse before we consider the
+/// clock source gone.
+const TIMEOUT_INTERVALS: u32 = 4;
+
+/// Second-order delay-locked loop used to smooth the BPM estimate derived
+/// from incoming MIDI clock pulses.
+///
+/// Unlike a flat rolling average, the DLL tracks the expected arrival time
+/// of the next clock (`t`) and a filtered clock period (`e2`), nudging both
+/// toward the observed timing on every pulse. This follows gradual tempo
+/// drift smoothly while resisting single-pulse jitter.
+struct TempoDll {
+    /// Expected arrival time of the next clock pulse.
+    t: Option<Instant>,
+    /// Filtered clock period estimate, in seconds per clock.
+    e2: Option<f64>,
+    /// Timestamp of the previous clock, used only to seed `e2` from the
+    /// first observed interval.
+    prev: Option<Instant>,
+    /// Loop bandwidth in Hz; controls the `b`/`c` filter coefficients.
+    bandwidth: f64,
+}
+
+impl TempoDll {
+    fn new(bandwidth: f64) -> Self {
+        Self {
+            t: None,
+            e2: None,
+            prev: None,
+            bandwidth,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.t = None;
+        self.e2 = None;
+        self.prev = None;
+    }
+
+    /// Feed an observed clock timestamp into the filter, returning the
+    /// filtered period estimate in seconds per clock, if established.
+    fn update(&mut self, obs: Instant) -> Option<f64> {
+        match (self.t, self.e2) {
+            (Some(t), Some(e2)) => {
+                // The loop updates once per clock, so the bandwidth is scaled
+                // by the clock period `e2`; unscaled coefficients would make
+                // the loop diverge on the first jittered pulse.
+                let omega = 2.0 * PI * self.bandwidth * e2;
+                let b = 2.0 * DLL_ZETA * omega;
+                let c = omega * omega;
+
+                // `obs` may be before or after `t`, so use a signed diff
+                // rather than `Instant::duration_since` (which saturates at
+                // zero).
+                let err = signed_diff_secs(obs, t);
+                let new_t = t + secs_to_duration(b * err + e2);
+                let new_e2 = e2 + c * err;
+                self.t = Some(new_t);
+                self.e2 = Some(new_e2);
+                Some(new_e2)
+            }
+            (None, _) => {
+                // No expected time yet: seed `e2` from the first interval
+                // once we have a previous timestamp, then arm `t`.
+                if let Some(prev) = self.prev {
+                    let interval = obs.duration_since(prev).as_secs_f64();
+                    if interval > 0.0 {
+                        self.e2 = Some(interval);
+                        self.t = Some(obs + secs_to_duration(interval));
+                    }
+                }
+                self.prev = Some(obs);
+                self.e2
+            }
+            (Some(_), None) => {
+                // Shouldn't happen (t is only set alongside e2), but handle
+                // defensively by re-seeding.
+                self.prev = Some(obs);
+                None
+            }
+        }
+    }
+
+    /// Current filtered period estimate, in seconds per clock.
+    fn period_secs(&self) -> Option<f64> {
+        self.e2
+    }
+}
+
+/// Signed elapsed seconds from `earlier` to `later` (positive if `later` is
+/// after `earlier`), since `Instant::duration_since` saturates at zero.
+fn signed_diff_secs(later: Instant, earlier: Instant) -> f64 {
+    if later >= earlier {
+        later.duration_since(earlier).as_secs_f64()
+    } else {
+        -earlier.duration_since(later).as_secs_f64()
+    }
+}
+
+fn secs_to_duration(secs: f64) -> std::time::Duration {
+    if secs <= 0.0 {
+        std::time::Duration::ZERO
+    } else {
+        std::time::Duration::from_secs_f64(secs)
+    }
+}
+
 /// Ring buffer for storing clock pulse timestamps.
 ///
 /// Used to calculate a rolling average BPM over the most recent bar of music.
 /// The buffer starts calculating BPM immediately with partial data, becoming
-/// more accurate as it fills.
+/// more accurate as it fills. Sized in clocks, so it holds exactly one bar
+/// of the configured time signature.
 pub struct ClockTimeBuffer {
-    times: [Option<Instant>; BPM_WINDOW_CLOCKS],
+    times: Vec<Option<Instant>>,
     index: usize,
     count: usize,
 }
 
 impl ClockTimeBuffer {
-    pub fn new() -> Self {
+    /// Create a buffer sized to hold `capacity` clock pulses (one bar, in
+    /// the configured time signature).
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
         Self {
-            times: [None; BPM_WINDOW_CLOCKS],
+            times: vec![None; capacity],
             index: 0,
             count: 0,
         }
@@ -42,10 +172,11 @@ impl ClockTimeBuffer {
 
     /// Add a timestamp to the buffer, returning the oldest timestamp and sample count.
     pub fn push(&mut self, time: Instant) -> (Option<Instant>, usize) {
+        let capacity = self.times.len();
         let oldest = self.times[self.index];
         self.times[self.index] = Some(time);
-        self.index = (self.index + 1) % BPM_WINDOW_CLOCKS;
-        if self.count < BPM_WINDOW_CLOCKS {
+        self.index = (self.index + 1) % capacity;
+        if self.count < capacity {
             self.count += 1;
         }
         (oldest, self.count)
@@ -53,10 +184,11 @@ impl ClockTimeBuffer {
 
     /// Get the oldest available timestamp for partial buffer calculation.
     pub fn get_oldest(&self) -> Option<(Instant, usize)> {
+        let capacity = self.times.len();
         if self.count == 0 {
             return None;
         }
-        if self.count < BPM_WINDOW_CLOCKS {
+        if self.count < capacity {
             // Buffer not full yet - oldest is at index 0
             self.times[0].map(|t| (t, self.count))
         } else {
@@ -66,7 +198,9 @@ impl ClockTimeBuffer {
     }
 
     pub fn clear(&mut self) {
-        self.times = [None; BPM_WINDOW_CLOCKS];
+        for slot in self.times.iter_mut() {
+            *slot = None;
+        }
         self.index = 0;
         self.count = 0;
     }
@@ -74,57 +208,158 @@ impl ClockTimeBuffer {
 
 impl Default for ClockTimeBuffer {
     fn default() -> Self {
-        Self::new()
+        Self::new(BPM_WINDOW_CLOCKS)
+    }
+}
+
+/// Transport state as defined by the MIDI spec's Start/Continue/Stop
+/// semantics: `MIDI_START` doesn't begin advancing position immediately,
+/// it arms the transport to start on the *next* clock pulse (mirroring the
+/// fix Ardour's MIDI clock slave applies).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportState {
+    /// No transport activity; position is not advancing.
+    Stopped,
+    /// `MIDI_START` received; position has been reset to 0 and we're
+    /// waiting for the first clock pulse to begin advancing.
+    Armed,
+    /// Position is advancing on every clock pulse.
+    Running,
+}
+
+impl TransportState {
+    fn to_u8(self) -> u8 {
+        match self {
+            TransportState::Stopped => 0,
+            TransportState::Armed => 1,
+            TransportState::Running => 2,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => TransportState::Armed,
+            2 => TransportState::Running,
+            _ => TransportState::Stopped,
+        }
     }
 }
 
 /// Shared clock state between MIDI thread and GUI.
 ///
 /// This struct tracks:
-/// - Whether playback is running
+/// - Transport state (stopped / armed / running)
 /// - Whether explicit transport messages have been received
 /// - Current position (clock count)
 /// - Calculated BPM
 ///
 /// # Auto-start Behavior
 /// If the application connects to a MIDI source that's already sending clock
-/// pulses (but hasn't sent a START message), it will auto-start. Once any
-/// explicit transport message (START/STOP/CONTINUE) is received, auto-start
-/// is disabled and only explicit transport controls playback.
+/// pulses (but hasn't sent a START message), it will auto-start directly into
+/// `Running` rather than going through `Armed`. Once any explicit transport
+/// message (START/STOP/CONTINUE) is received, auto-start is disabled and only
+/// explicit transport controls playback.
 #[derive(Clone)]
 pub struct ClockState {
-    running: Arc<AtomicBool>,
+    state: Arc<AtomicU8>,
     seen_transport: Arc<AtomicBool>,
     clock_count: Arc<AtomicU64>,
     bpm_x100: Arc<AtomicU64>,
     clock_times: Arc<std::sync::Mutex<ClockTimeBuffer>>,
+    dll: Arc<std::sync::Mutex<TempoDll>>,
+    last_clock: Arc<std::sync::Mutex<Option<Instant>>>,
+    /// Clock position decoded from the most recent Song Position Pointer,
+    /// consumed by the next `MIDI_CONTINUE`.
+    pending_spp_clocks: Arc<std::sync::Mutex<Option<u64>>>,
+    /// Beats per bar (time signature numerator).
+    time_sig_numerator: Arc<AtomicU8>,
+    /// Time signature denominator (4 = quarter note, 8 = eighth, ...), used
+    /// to derive clocks-per-beat for position math.
+    time_sig_denominator: Arc<AtomicU8>,
 }
 
 impl ClockState {
     pub fn new() -> Self {
+        Self::with_bandwidth(DEFAULT_DLL_BANDWIDTH_HZ)
+    }
+
+    /// Create a new `ClockState` with a custom tempo-DLL bandwidth (Hz).
+    /// Higher values track tempo changes faster at the cost of more jitter.
+    /// Assumes a 4/4 time signature; see [`Self::with_bandwidth_and_signature`]
+    /// for other meters.
+    pub fn with_bandwidth(bandwidth_hz: f64) -> Self {
+        Self::with_bandwidth_and_signature(bandwidth_hz, (BEATS_PER_BAR as u8, 4))
+    }
+
+    /// Create a new `ClockState` with a custom tempo-DLL bandwidth (Hz) and
+    /// time signature `(numerator, denominator)`. The rolling BPM window is
+    /// sized to exactly one bar of the given meter.
+    pub fn with_bandwidth_and_signature(bandwidth_hz: f64, time_signature: (u8, u8)) -> Self {
+        let (numerator, denominator) = time_signature;
+        let clocks_per_bar = numerator as usize * clocks_per_beat(denominator) as usize;
         Self {
-            running: Arc::new(AtomicBool::new(false)),
+            state: Arc::new(AtomicU8::new(TransportState::Stopped.to_u8())),
             seen_transport: Arc::new(AtomicBool::new(false)),
             clock_count: Arc::new(AtomicU64::new(0)),
             bpm_x100: Arc::new(AtomicU64::new(0)),
-            clock_times: Arc::new(std::sync::Mutex::new(ClockTimeBuffer::new())),
+            clock_times: Arc::new(std::sync::Mutex::new(ClockTimeBuffer::new(clocks_per_bar))),
+            dll: Arc::new(std::sync::Mutex::new(TempoDll::new(bandwidth_hz))),
+            last_clock: Arc::new(std::sync::Mutex::new(None)),
+            pending_spp_clocks: Arc::new(std::sync::Mutex::new(None)),
+            time_sig_numerator: Arc::new(AtomicU8::new(numerator)),
+            time_sig_denominator: Arc::new(AtomicU8::new(denominator)),
         }
     }
 
+    /// True once the transport is actually advancing (`Running`). Note this
+    /// is false while `Armed` — waiting for the first clock after a START.
     pub fn is_running(&self) -> bool {
-        self.running.load(Ordering::SeqCst)
+        self.transport_state() == TransportState::Running
+    }
+
+    /// Current transport state, for UI that wants to distinguish "armed,
+    /// waiting for clock" from "running".
+    pub fn transport_state(&self) -> TransportState {
+        TransportState::from_u8(self.state.load(Ordering::SeqCst))
+    }
+
+    /// True if the transport has been started and just hasn't received its
+    /// first clock pulse yet (`Armed`) or is already advancing (`Running`).
+    /// A clock generator should keep producing pulses in either case --
+    /// it's what gets an `Armed` transport into `Running` in the first place.
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self.transport_state(),
+            TransportState::Armed | TransportState::Running
+        )
+    }
+
+    fn set_state(&self, state: TransportState) {
+        self.state.store(state.to_u8(), Ordering::SeqCst);
     }
 
     pub fn get_clock_count(&self) -> u64 {
         self.clock_count.load(Ordering::SeqCst)
     }
 
-    /// Get current position as (bar, beat) tuple, both 1-indexed.
+    /// Current time signature as `(numerator, denominator)`.
+    pub fn time_signature(&self) -> (u8, u8) {
+        (
+            self.time_sig_numerator.load(Ordering::SeqCst),
+            self.time_sig_denominator.load(Ordering::SeqCst),
+        )
+    }
+
+    /// Get current position as (bar, beat) tuple, both 1-indexed, using the
+    /// configured time signature (beats-per-bar from the numerator,
+    /// clocks-per-beat derived from the denominator).
     pub fn get_position(&self) -> (u64, u64) {
         let count = self.clock_count.load(Ordering::SeqCst);
-        let beats = count / CLOCKS_PER_BEAT;
-        let bar = (beats / BEATS_PER_BAR) + 1;
-        let beat_in_bar = (beats % BEATS_PER_BAR) + 1;
+        let beats_per_bar = self.time_sig_numerator.load(Ordering::SeqCst) as u64;
+        let clocks_per_beat = clocks_per_beat(self.time_sig_denominator.load(Ordering::SeqCst));
+        let beats = count / clocks_per_beat;
+        let bar = (beats / beats_per_bar) + 1;
+        let beat_in_bar = (beats % beats_per_bar) + 1;
         (bar, beat_in_bar)
     }
 
@@ -146,50 +381,126 @@ impl ClockState {
         match message[0] {
             MIDI_START => {
                 self.seen_transport.store(true, Ordering::SeqCst);
-                self.running.store(true, Ordering::SeqCst);
+                // Reset and arm; position only starts advancing on the next
+                // clock pulse (see `TransportState`).
+                self.set_state(TransportState::Armed);
                 self.clock_count.store(0, Ordering::SeqCst);
                 self.bpm_x100.store(0, Ordering::SeqCst);
                 self.clock_times.lock().unwrap().clear();
+                self.dll.lock().unwrap().reset();
+                *self.pending_spp_clocks.lock().unwrap() = None;
             }
             MIDI_CONTINUE => {
                 self.seen_transport.store(true, Ordering::SeqCst);
-                self.running.store(true, Ordering::SeqCst);
+                self.set_state(TransportState::Running);
+
+                // If a Song Position Pointer arrived before this Continue,
+                // resume at the bar/beat it specified instead of wherever
+                // `clock_count` happened to be left.
+                if let Some(clocks) = self.pending_spp_clocks.lock().unwrap().take() {
+                    self.clock_count.store(clocks, Ordering::SeqCst);
+                }
+            }
+            MIDI_SPP => {
+                if message.len() < 3 {
+                    return;
+                }
+                let spp = decode_spp(message[1], message[2]);
+                *self.pending_spp_clocks.lock().unwrap() = Some(spp_to_clocks(spp));
             }
             MIDI_STOP => {
                 self.seen_transport.store(true, Ordering::SeqCst);
-                self.running.store(false, Ordering::SeqCst);
+                self.set_state(TransportState::Stopped);
             }
             MIDI_CLOCK => {
-                // Auto-start on clock only if we haven't seen explicit transport yet
-                if !self.seen_transport.load(Ordering::SeqCst) {
-                    self.running.store(true, Ordering::SeqCst);
+                let mut just_armed = false;
+                match self.transport_state() {
+                    TransportState::Armed => {
+                        // First clock after START: start advancing, but this
+                        // pulse itself counts as clock 0, not clock 1.
+                        self.set_state(TransportState::Running);
+                        just_armed = true;
+                    }
+                    TransportState::Stopped if !self.seen_transport.load(Ordering::SeqCst) => {
+                        // Auto-start path: no explicit transport seen yet, so
+                        // a bare clock stream starts playback immediately
+                        // (preserving the pre-existing auto-start behavior).
+                        self.set_state(TransportState::Running);
+                    }
+                    _ => {}
                 }
 
-                // Always calculate BPM from clock pulses (even when stopped)
-                let mut buffer = self.clock_times.lock().unwrap();
-                buffer.push(now);
-
-                if let Some((oldest_time, sample_count)) = buffer.get_oldest() {
-                    if sample_count > 1 {
-                        let elapsed = now.duration_since(oldest_time).as_secs_f64();
-                        if elapsed > 0.0 {
-                            let clocks = (sample_count - 1) as f64;
-                            let beats = clocks / CLOCKS_PER_BEAT as f64;
-                            let minutes = elapsed / 60.0;
-                            let bpm = beats / minutes;
-                            self.bpm_x100.store((bpm * 100.0) as u64, Ordering::SeqCst);
-                        }
-                    }
+                // Always track clock pulses (even when stopped)
+                self.clock_times.lock().unwrap().push(now);
+                *self.last_clock.lock().unwrap() = Some(now);
+
+                // Feed the tempo DLL; it smooths the period estimate rather
+                // than averaging over a flat window, so BPM tracks gradual
+                // tempo changes without jittering on single-pulse noise.
+                if let Some(period_secs) = self.dll.lock().unwrap().update(now) {
+                    let bpm = 60.0 / (period_secs * CLOCKS_PER_BEAT as f64);
+                    self.bpm_x100.store((bpm * 100.0) as u64, Ordering::SeqCst);
                 }
 
-                // Only count position when running
-                if self.running.load(Ordering::SeqCst) {
+                // Only count position when running, and skip the pulse that
+                // just transitioned us out of Armed (it's clock 0).
+                if self.is_running() && !just_armed {
                     self.clock_count.fetch_add(1, Ordering::SeqCst);
                 }
             }
             _ => {}
         }
     }
+
+    /// Directly set transport state, position, and tempo from a non-MIDI
+    /// clock source (currently: the JACK timebase, see `jack_backend`).
+    /// JACK publishes bar/beat/tick and BPM as first-class transport state
+    /// rather than a pulse stream, so there's no `&[u8]` to feed through
+    /// `handle_midi_message`; this bypasses it and writes the derived values
+    /// straight through, same as the MIDI path would after decoding them.
+    #[cfg_attr(not(feature = "jack"), allow(dead_code))]
+    pub fn set_external_position(&self, clock_count: u64, bpm: f64, state: TransportState) {
+        self.seen_transport.store(true, Ordering::SeqCst);
+        self.set_state(state);
+        self.clock_count.store(clock_count, Ordering::SeqCst);
+        self.bpm_x100.store((bpm * 100.0) as u64, Ordering::SeqCst);
+    }
+
+    /// Watchdog for a clock source that stops sending `MIDI_CLOCK` without
+    /// ever sending `MIDI_STOP` (unplugged cable, crashed DAW, etc). If more
+    /// than [`TIMEOUT_INTERVALS`] expected clock periods have elapsed since
+    /// the last pulse, this clears `running`, zeroes the BPM display, and
+    /// resets the timing buffers so stale values don't linger.
+    ///
+    /// Call this periodically (e.g. from the GUI tick) rather than only in
+    /// response to incoming messages, since a dropout is by definition the
+    /// absence of messages.
+    pub fn check_timeout(&self, now: Instant) {
+        if self.transport_state() == TransportState::Stopped {
+            return;
+        }
+
+        let last_clock = *self.last_clock.lock().unwrap();
+        let Some(last_clock) = last_clock else {
+            return;
+        };
+
+        let period = self
+            .dll
+            .lock()
+            .unwrap()
+            .period_secs()
+            .map(secs_to_duration)
+            .unwrap_or(std::time::Duration::ZERO);
+        let timeout = (period * TIMEOUT_INTERVALS).max(MIN_TIMEOUT);
+
+        if now.duration_since(last_clock) > timeout {
+            self.set_state(TransportState::Stopped);
+            self.bpm_x100.store(0, Ordering::SeqCst);
+            self.clock_times.lock().unwrap().clear();
+            self.dll.lock().unwrap().reset();
+        }
+    }
 }
 
 impl Default for ClockState {
@@ -223,11 +534,33 @@ mod tests {
         assert_eq!(state.get_clock_count(), 1);
     }
 
+    #[test]
+    fn test_start_arms_then_clock_runs() {
+        let state = ClockState::new();
+
+        // START arms the transport but doesn't start advancing yet.
+        state.handle_midi_message(&[MIDI_START]);
+        assert_eq!(state.transport_state(), TransportState::Armed);
+        assert!(!state.is_running());
+
+        // The first clock after START transitions to Running and counts as
+        // clock 0, not clock 1.
+        state.handle_midi_message(&[MIDI_CLOCK]);
+        assert_eq!(state.transport_state(), TransportState::Running);
+        assert!(state.is_running());
+        assert_eq!(state.get_clock_count(), 0);
+
+        // Subsequent clocks advance position normally.
+        state.handle_midi_message(&[MIDI_CLOCK]);
+        assert_eq!(state.get_clock_count(), 1);
+    }
+
     #[test]
     fn test_stop_prevents_auto_start() {
         let state = ClockState::new();
 
         state.handle_midi_message(&[MIDI_START]);
+        state.handle_midi_message(&[MIDI_CLOCK]);
         assert!(state.is_running());
 
         state.handle_midi_message(&[MIDI_STOP]);
@@ -276,8 +609,9 @@ mod tests {
         let state = ClockState::new();
         state.handle_midi_message(&[MIDI_START]);
 
-        // 24 clocks = 1 beat
-        for _ in 0..24 {
+        // The first clock only arms Running (counts as clock 0), so reaching
+        // clock 24 (1 beat) takes 25 pulses.
+        for _ in 0..25 {
             state.handle_midi_message(&[MIDI_CLOCK]);
         }
         assert_eq!(state.get_position(), (1, 2));
@@ -346,4 +680,124 @@ mod tests {
         assert!(!state.is_running());
         assert_eq!(state.get_clock_count(), 0);
     }
+
+    #[test]
+    fn test_timeout_stops_running_after_dropout() {
+        let state = ClockState::new();
+        state.handle_midi_message(&[MIDI_START]);
+
+        let start = Instant::now();
+        let clock_interval = Duration::from_micros(20833); // 120 BPM
+        for i in 0..10 {
+            state.handle_midi_message_at(&[MIDI_CLOCK], start + clock_interval * i);
+        }
+        assert!(state.is_running());
+        assert!(state.get_bpm() > 0.0);
+
+        // No more clocks arrive; a long silence should trip the watchdog.
+        let silence = start + clock_interval * 10 + Duration::from_secs(1);
+        state.check_timeout(silence);
+
+        assert!(!state.is_running());
+        assert_eq!(state.get_bpm(), 0.0);
+    }
+
+    #[test]
+    fn test_timeout_does_not_trigger_before_threshold() {
+        let state = ClockState::new();
+        state.handle_midi_message(&[MIDI_START]);
+
+        let start = Instant::now();
+        let clock_interval = Duration::from_micros(20833);
+        for i in 0..10 {
+            state.handle_midi_message_at(&[MIDI_CLOCK], start + clock_interval * i);
+        }
+
+        // Barely any time has passed - should not be considered a dropout.
+        let soon = start + clock_interval * 10 + Duration::from_millis(10);
+        state.check_timeout(soon);
+
+        assert!(state.is_running());
+    }
+
+    #[test]
+    fn test_spp_resumes_position_on_continue() {
+        let state = ClockState::new();
+        state.handle_midi_message(&[MIDI_START]);
+        state.handle_midi_message(&[MIDI_STOP]);
+
+        // Position the song at SPP 8 (8 * 6 = 48 clocks = bar 1, beat 3)
+        state.handle_midi_message(&[MIDI_SPP, 8, 0]);
+        assert_eq!(state.get_clock_count(), 0, "SPP alone shouldn't move position");
+
+        state.handle_midi_message(&[MIDI_CONTINUE]);
+        assert!(state.is_running());
+        assert_eq!(state.get_clock_count(), 48);
+        assert_eq!(state.get_position(), (1, 3));
+    }
+
+    #[test]
+    fn test_continue_without_spp_keeps_position() {
+        let state = ClockState::new();
+        state.handle_midi_message(&[MIDI_START]);
+        for _ in 0..30 {
+            state.handle_midi_message(&[MIDI_CLOCK]);
+        }
+        state.handle_midi_message(&[MIDI_STOP]);
+        let count_before = state.get_clock_count();
+
+        state.handle_midi_message(&[MIDI_CONTINUE]);
+        assert_eq!(state.get_clock_count(), count_before);
+    }
+
+    #[test]
+    fn test_start_clears_pending_spp() {
+        let state = ClockState::new();
+        state.handle_midi_message(&[MIDI_SPP, 8, 0]);
+        state.handle_midi_message(&[MIDI_START]);
+        state.handle_midi_message(&[MIDI_CONTINUE]);
+        assert_eq!(state.get_clock_count(), 0);
+    }
+
+    #[test]
+    fn test_timeout_noop_when_already_stopped() {
+        let state = ClockState::new();
+        assert!(!state.is_running());
+        state.check_timeout(Instant::now());
+        assert!(!state.is_running());
+    }
+
+    #[test]
+    fn test_position_in_three_four() {
+        let state = ClockState::with_bandwidth_and_signature(DEFAULT_DLL_BANDWIDTH_HZ, (3, 4));
+        assert_eq!(state.time_signature(), (3, 4));
+        state.handle_midi_message(&[MIDI_START]);
+
+        // First clock only arms Running (clock 0); 3 beats/bar * 24
+        // clocks/beat = 72 clocks/bar in 3/4.
+        for _ in 0..73 {
+            state.handle_midi_message(&[MIDI_CLOCK]);
+        }
+        assert_eq!(state.get_position(), (2, 1));
+    }
+
+    #[test]
+    fn test_position_in_six_eight() {
+        let state = ClockState::with_bandwidth_and_signature(DEFAULT_DLL_BANDWIDTH_HZ, (6, 8));
+        assert_eq!(state.time_signature(), (6, 8));
+        state.handle_midi_message(&[MIDI_START]);
+
+        // Eighth-note beats are 12 clocks; 6 beats/bar * 12 clocks/beat = 72
+        // clocks/bar. First clock arms (clock 0), so the 13th clock lands on
+        // beat 2.
+        for _ in 0..13 {
+            state.handle_midi_message(&[MIDI_CLOCK]);
+        }
+        assert_eq!(state.get_position(), (1, 2));
+
+        for _ in 0..(72 - 12) {
+            state.handle_midi_message(&[MIDI_CLOCK]);
+        }
+        assert_eq!(state.get_position(), (2, 1));
+    }
 }
@@ -25,6 +25,40 @@ fn default_repeat_count() -> u32 {
     1
 }
 
+/// Where the looper gets its MIDI clock from.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ClockSource {
+    /// Follow clock pulses received on the MIDI input (the default).
+    External,
+    /// Generate clock internally at the given BPM and act as transport
+    /// master, sending MIDI_CLOCK/START/STOP on the output.
+    Internal { bpm: f64 },
+}
+
+impl Default for ClockSource {
+    fn default() -> Self {
+        ClockSource::External
+    }
+}
+
+/// Which MIDI I/O implementation the looper talks through.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MidiBackend {
+    /// `midir`-based hardware/IAC ports (the default, works everywhere).
+    Midir,
+    /// JACK MIDI ports, synced to the JACK session's shared transport
+    /// instead of (or in addition to) MIDI clock. Only available when the
+    /// binary is built with the `jack` feature; requests for it otherwise
+    /// fall back to `Midir`.
+    Jack,
+}
+
+impl Default for MidiBackend {
+    fn default() -> Self {
+        MidiBackend::Midir
+    }
+}
+
 /// Complete looper configuration.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct LooperConfig {
@@ -42,12 +76,46 @@ pub struct LooperConfig {
     /// Slot configurations, keyed by slot letter (A-Z)
     #[serde(default)]
     pub slots: BTreeMap<char, SlotConfig>,
+    /// Tempo DLL bandwidth in Hz, trading tracking speed against stability.
+    /// Higher values follow tempo changes faster but are noisier; lower
+    /// values are smoother but slower to settle.
+    #[serde(default = "default_clock_bandwidth_hz")]
+    pub clock_bandwidth_hz: f64,
+    /// Whether to follow an external MIDI clock or generate one internally.
+    #[serde(default)]
+    pub clock_source: ClockSource,
+    /// Time signature as `(numerator, denominator)`, e.g. `(4, 4)` or
+    /// `(6, 8)`. Drives bar/beat position math and sizes the clock-timestamp
+    /// window to one bar.
+    #[serde(default = "default_time_signature")]
+    pub time_signature: (u8, u8),
+    /// Which MIDI I/O backend to use.
+    #[serde(default)]
+    pub midi_backend: MidiBackend,
+    /// Fraction of a swing grid subdivision that every other subdivision is
+    /// delayed by (0.0 = no swing, 1.0 = a full subdivision late). See
+    /// `SequencePlayer::set_swing`.
+    #[serde(default)]
+    pub swing_ratio: f64,
+    /// Size, in clock ticks, of the grid swing is applied to (e.g. `6` for
+    /// a sixteenth-note grid at 24 ppqn). `0` disables swing regardless of
+    /// `swing_ratio`.
+    #[serde(default)]
+    pub swing_grid_ticks: u64,
 }
 
 fn default_channel() -> u8 {
     1 // 1-indexed for YAML readability
 }
 
+fn default_clock_bandwidth_hz() -> f64 {
+    crate::clock::DEFAULT_DLL_BANDWIDTH_HZ
+}
+
+fn default_time_signature() -> (u8, u8) {
+    (4, 4)
+}
+
 impl LooperConfig {
     /// Get the default config file path.
     pub fn default_path() -> PathBuf {
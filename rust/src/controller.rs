@@ -0,0 +1,88 @@
+//! Grid-controller (Push/Launchpad-style) control surface support.
+//!
+//! Maps a hardware pad grid's note-on messages onto `SlotId`s so a clip
+//! launcher can arm/trigger slots through the same quantized-launch path as
+//! the on-screen sequence table, and builds note-on "LED" feedback messages
+//! that mirror each slot's state back to the pads.
+
+use crate::playback::{PlaybackState, SequenceGrid, SlotId};
+
+/// LED velocity for an unlit pad (empty slot, or a queued slot's "off" blink
+/// phase); velocity 0 is the "note-on means note-off" convention most
+/// controllers use.
+const LED_OFF: u8 = 0;
+/// Steady, dim color for a slot with a loop loaded but not playing.
+const LED_LOADED_IDLE: u8 = 20;
+/// Bright color for the currently playing slot.
+const LED_PLAYING: u8 = 60;
+/// Bright color for a queued slot's "on" blink phase.
+const LED_QUEUED_BRIGHT: u8 = 65;
+
+/// Maps a controller's linear pad row onto slot IDs, and builds the note-on
+/// "LED" feedback messages that color each pad by slot state.
+#[derive(Debug, Clone, Copy)]
+pub struct PadGridConfig {
+    /// MIDI note number of the first pad (slot A).
+    pub base_note: u8,
+    /// MIDI channel the controller's pads send/receive on (0-15).
+    pub channel: u8,
+}
+
+impl Default for PadGridConfig {
+    fn default() -> Self {
+        // Note 36 (C1) matches Ableton Push's clip-grid origin.
+        Self {
+            base_note: 36,
+            channel: 0,
+        }
+    }
+}
+
+impl PadGridConfig {
+    /// Map an incoming pad note to the slot it represents, if any.
+    pub fn note_to_slot(&self, note: u8) -> Option<SlotId> {
+        let offset = note.checked_sub(self.base_note)?;
+        SlotId::from_index(offset as usize)
+    }
+
+    /// Map a slot back to the pad note that represents it.
+    fn slot_to_note(&self, slot_id: SlotId) -> Option<u8> {
+        self.base_note.checked_add(slot_id.index() as u8)
+    }
+
+    /// Build note-on "LED" messages mirroring every slot's current state:
+    /// off for empty slots, dim-steady for loaded-but-idle, bright for the
+    /// currently playing slot, and a color that alternates with `blink_on`
+    /// for a slot queued via `pending_launch`.
+    pub fn build_led_feedback(
+        &self,
+        grid: &SequenceGrid,
+        playback_state: Option<PlaybackState>,
+        pending_launch: Option<SlotId>,
+        blink_on: bool,
+    ) -> Vec<Vec<u8>> {
+        let current_slot = playback_state.map(|s| s.current_slot);
+        let status = 0x90 | (self.channel & 0x0F);
+
+        grid.slots
+            .iter()
+            .filter_map(|slot| {
+                let note = self.slot_to_note(slot.id)?;
+                let velocity = if pending_launch == Some(slot.id) {
+                    if blink_on {
+                        LED_QUEUED_BRIGHT
+                    } else {
+                        LED_OFF
+                    }
+                } else if current_slot == Some(slot.id) {
+                    LED_PLAYING
+                } else if slot.has_loop() {
+                    LED_LOADED_IDLE
+                } else {
+                    LED_OFF
+                };
+                Some(vec![status, note, velocity])
+            })
+            .collect()
+    }
+}
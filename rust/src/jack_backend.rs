@@ -0,0 +1,213 @@
+//! Optional JACK MIDI backend.
+//!
+//! `midir`/IAC is macOS-centric and can't participate in a JACK session's
+//! shared transport. When built with the `jack` feature, this module
+//! registers JACK MIDI in/out ports and hooks the JACK timebase so
+//! `ClockState` follows JACK's frame position (bar/beat/BPM) instead of
+//! (or in addition to) MIDI clock bytes, and `SequencePlayer::tick` is
+//! advanced from the JACK process callback rather than a generator thread.
+//!
+//! In MASTER mode the looper registers as JACK timebase master, publishing
+//! BBT and tempo for every other client in the session.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use jack::{
+    Client, ClientOptions, Control, MidiIn, MidiOut as JackMidiOut, PortSpec, ProcessHandler,
+    ProcessScope, TimebaseHandler, TransportState as JackTransportState,
+};
+
+use crate::clock::{ClockState, TransportState};
+use crate::controller::PadGridConfig;
+use crate::midi::{self, clocks_per_beat, MidiInputContext};
+use crate::playback::SequencePlayer;
+use crate::remote::{RemoteAction, RemoteControlConfig};
+
+/// A live JACK client plus the in/out MIDI ports registered on it. Dropping
+/// this deactivates the client and unregisters its ports.
+pub struct JackBackend {
+    pub in_port_name: String,
+    pub out_port_name: String,
+    _async_client: jack::AsyncClient<(), LooperProcessHandler>,
+}
+
+/// Runs on JACK's realtime thread once per process cycle. Reads the
+/// transport's current BBT/tempo, writes it into `clock_state` (replacing
+/// the generator-thread-driven path used by the `midir` backend), and
+/// advances `sequence_player` with one `tick` at the transport's current
+/// 24-ppqn clock whenever that clock changed since the previous cycle
+/// (clocks skipped in between are not ticked one by one), sending the
+/// resulting events out the JACK MIDI port.
+struct LooperProcessHandler {
+    clock_state: ClockState,
+    sequence_player: Arc<Mutex<SequencePlayer>>,
+    master_mode: Arc<std::sync::atomic::AtomicBool>,
+    midi_in: jack::Port<MidiIn>,
+    midi_out: jack::Port<JackMidiOut>,
+    last_clock_count: u64,
+    /// Everything `midi::handle_incoming_midi_message` needs to translate an
+    /// incoming byte into a remote action, a pad hit, or a recorded event --
+    /// the same gesture handling the `midir` backend runs (see
+    /// `main::start_midi_listener`). Its `midi_out` is permanently `None`:
+    /// JACK MIDI output can only be written through the `scope`-bound
+    /// `writer` below, not a `midir` connection, so the shared pipeline's
+    /// clock-tick-triggers-events branch is a no-op here -- playback is
+    /// already advanced from BBT further down in `process`, not from
+    /// incoming clock bytes.
+    midi_ctx: MidiInputContext,
+}
+
+impl ProcessHandler for LooperProcessHandler {
+    fn process(&mut self, client: &Client, scope: &ProcessScope) -> Control {
+        // Forward each raw incoming JACK MIDI message through the same
+        // gesture pipeline the `midir` backend uses (see
+        // `main::start_midi_listener`), so a pad/remote controller or
+        // recording input patched into the `looper-in` JACK port works
+        // identically to one patched into the midir port.
+        for raw in self.midi_in.iter(scope) {
+            midi::handle_incoming_midi_message(&self.midi_ctx, raw.bytes);
+        }
+
+        let (position, transport_state) = client.transport_query();
+        let bbt = position.bbt();
+        let bpm = bbt.map(|b| b.beats_per_minute).unwrap_or(crate::midi::DEFAULT_MASTER_BPM);
+
+        let denominator = self.clock_state.time_signature().1;
+        let beat_clocks = clocks_per_beat(denominator);
+        let clock_count = bbt
+            .map(|b| {
+                let beats = (b.bar.saturating_sub(1)) as u64 * b.sig_num as u64
+                    + (b.beat.saturating_sub(1)) as u64;
+                beats * beat_clocks + (b.tick as u64 * beat_clocks) / b.ticks_per_beat.max(1.0) as u64
+            })
+            .unwrap_or(self.last_clock_count);
+
+        let state = match transport_state {
+            JackTransportState::Rolling => TransportState::Running,
+            JackTransportState::Starting => TransportState::Armed,
+            _ => TransportState::Stopped,
+        };
+        self.clock_state.set_external_position(clock_count, bpm, state);
+
+        let mut writer = self.midi_out.writer(scope);
+        if state == TransportState::Running && clock_count != self.last_clock_count {
+            let events = {
+                let mut player = self.sequence_player.lock().unwrap();
+                player.tick(clock_count)
+            };
+            for event in events {
+                let _ = writer.write(&jack::RawMidi {
+                    time: 0,
+                    bytes: &event,
+                });
+            }
+        }
+        self.last_clock_count = clock_count;
+
+        Control::Continue
+    }
+}
+
+/// Registers the looper as JACK's timebase master when `master_mode` is
+/// set, publishing BBT/tempo derived from `ClockState` for every other
+/// client in the session. JACK calls `timebase` once per cycle on the
+/// client that holds the master role; if another client is already master
+/// this handler is simply never invoked.
+struct LooperTimebaseHandler {
+    clock_state: ClockState,
+}
+
+impl TimebaseHandler for LooperTimebaseHandler {
+    fn timebase(
+        &mut self,
+        _state: JackTransportState,
+        _n_frames: jack::Frames,
+        pos: &mut jack::Position,
+        _is_new_pos: bool,
+    ) {
+        let (beats_per_bar, denominator) = self.clock_state.time_signature();
+        let (bar, beat) = self.clock_state.get_position();
+        pos.bar = bar as i32;
+        pos.beat = beat as i32;
+        pos.tick = 0;
+        pos.sig_num = beats_per_bar as f32 as u32;
+        pos.sig_denom = denominator as u32;
+        pos.beats_per_minute = self.clock_state.get_bpm();
+    }
+}
+
+impl JackBackend {
+    /// Connect to the JACK server, register `looper-in`/`looper-out` MIDI
+    /// ports, and start the process loop. If `master_mode` is set at the
+    /// moment the client activates, register as timebase master as well.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        clock_state: ClockState,
+        sequence_player: Arc<Mutex<SequencePlayer>>,
+        master_mode: Arc<std::sync::atomic::AtomicBool>,
+        screenshot_requested: Arc<AtomicBool>,
+        pad_config: PadGridConfig,
+        remote_config: RemoteControlConfig,
+        pending_remote_actions: Arc<Mutex<VecDeque<RemoteAction>>>,
+    ) -> Result<Self, String> {
+        let (client, _status) = Client::new("looper", ClientOptions::NO_START_SERVER)
+            .map_err(|e| format!("Failed to connect to JACK server: {}", e))?;
+
+        let midi_in = client
+            .register_port("looper-in", MidiIn::default())
+            .map_err(|e| format!("Failed to register JACK MIDI in port: {}", e))?;
+        let midi_out = client
+            .register_port("looper-out", JackMidiOut::default())
+            .map_err(|e| format!("Failed to register JACK MIDI out port: {}", e))?;
+        let in_port_name = midi_in.name().unwrap_or_else(|_| "looper-in".to_string());
+        let out_port_name = midi_out.name().unwrap_or_else(|_| "looper-out".to_string());
+
+        let midi_ctx = MidiInputContext {
+            clock_state: clock_state.clone(),
+            sequence_player: sequence_player.clone(),
+            midi_out: Arc::new(Mutex::new(None)),
+            master_mode: master_mode.clone(),
+            screenshot_requested,
+            pad_config,
+            remote_config,
+            pending_remote_actions,
+        };
+
+        let handler = LooperProcessHandler {
+            clock_state: clock_state.clone(),
+            sequence_player,
+            master_mode: master_mode.clone(),
+            midi_in,
+            midi_out,
+            last_clock_count: 0,
+            midi_ctx,
+        };
+
+        let async_client = client
+            .activate_async((), handler)
+            .map_err(|e| format!("Failed to activate JACK client: {}", e))?;
+
+        if master_mode.load(Ordering::SeqCst) {
+            let _ = async_client
+                .as_client()
+                .become_timebase_master(LooperTimebaseHandler { clock_state });
+        }
+
+        Ok(Self {
+            in_port_name,
+            out_port_name,
+            _async_client: async_client,
+        })
+    }
+
+    /// (Re-)claim the JACK timebase master role, e.g. when the user toggles
+    /// the looper into master mode after startup. Safe to call repeatedly.
+    pub fn claim_timebase_master(&self, clock_state: ClockState) {
+        let _ = self
+            ._async_client
+            .as_client()
+            .become_timebase_master(LooperTimebaseHandler { clock_state });
+    }
+}
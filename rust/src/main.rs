@@ -3,27 +3,56 @@
 //! This application connects to a MIDI input (preferring IAC Driver on macOS),
 //! loads a MIDI loop, and plays it back in sync with the external clock.
 
+mod arp;
 mod clock;
+mod config;
+mod controller;
+#[cfg(feature = "jack")]
+mod jack_backend;
 mod midi;
 mod playback;
+mod remote;
 mod ui;
 
+use std::collections::VecDeque;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use iced::keyboard::{self, key::Named, Key};
 use iced::time::{self, milliseconds};
-use iced::widget::{button, column, container, row, text};
+use iced::widget::{button, column, container, row, scrollable, text};
 use iced::window::{self, Screenshot};
 use iced::{Center, Element, Fill, Subscription, Task, Theme};
 use midir::MidiInput;
 
 use clock::ClockState;
-use midi::MidiOut;
-use playback::{Loop, Sequence, SequenceEntry, SequenceGrid, SequencePlayer, SlotId};
-use ui::{view_sequence_table, QuanEditState};
+use config::LooperConfig;
+use controller::PadGridConfig;
+use midi::{MidiOut, BEATS_PER_BAR, CLOCKS_PER_BEAT};
+use playback::{
+    write_smf, LaunchQuantum, Loop, QueueQuantum, Sequence, SequenceEntry, SequenceGrid,
+    SequencePlayer, SlotId, Step, TimeDivision, Track,
+};
+use remote::{RemoteAction, RemoteControlConfig};
+use ui::{
+    cycle_column_page, cycle_loop_index, cycle_next_slot, scroll_offset_for_slot, select_next,
+    select_prev, table_scroll_id, view_sequence_table, QuanEditState,
+};
+
+/// A queued slot launch blinks at this period to stand out from a slot
+/// that's merely playing or NEXT-linked.
+const LAUNCH_BLINK_PERIOD: Duration = Duration::from_millis(300);
+
+/// Lower/upper bound a tap-tempo or BPM-control adjustment is clamped to.
+const MIN_MASTER_BPM: f32 = 40.0;
+const MAX_MASTER_BPM: f32 = 300.0;
+
+/// Taps older than this are discarded before averaging a new tap-tempo
+/// interval, so a long pause between taps starts a fresh estimate instead of
+/// blending with a stale one.
+const TAP_TEMPO_WINDOW: Duration = Duration::from_secs(2);
 
 fn main() -> iced::Result {
     iced::application(Looper::new, Looper::update, Looper::view)
@@ -41,18 +70,57 @@ struct Looper {
     in_port_name: String,
     out_port_name: String,
     master_mode: Arc<AtomicBool>,
+    // Live master-clock tempo in milli-BPM (e.g. 120_000 = 120 BPM), shared
+    // with the clock-generator thread so UI/tap-tempo changes take effect
+    // immediately.
+    master_bpm_milli: Arc<AtomicU32>,
+    // Recent tap-tempo key presses, used to average an inter-tap interval.
+    tap_times: Vec<Instant>,
+    // App start time, used to drive the queued-launch blink timer.
+    app_start: Instant,
+    // Pad-grid controller mapping, for both inbound slot triggers and
+    // outbound LED feedback.
+    pad_config: PadGridConfig,
     // Keep connections alive
     _midi_in_connection: Option<midir::MidiInputConnection<()>>,
     midi_out: Arc<Mutex<Option<MidiOut>>>,
+    // Live JACK MIDI ports and timebase sync, when built with the `jack`
+    // feature and configured to use it. Runs alongside the midir ports
+    // above rather than replacing them, so JACK clients get sample-accurate
+    // transport sync without losing the existing IAC/hardware path.
+    #[cfg(feature = "jack")]
+    _jack_backend: Option<jack_backend::JackBackend>,
+    // Human-readable name of the active MIDI backend(s), shown in the
+    // status line.
+    backend_label: String,
     // Sequence grid for UI
     sequence_grid: SequenceGrid,
     // Screenshot request flag (set by MIDI CC 119)
     screenshot_requested: Arc<AtomicBool>,
+    // Config-driven CC -> table-edit action mapping for incoming
+    // control-surface gestures.
+    remote_config: RemoteControlConfig,
+    // Table-edit actions queued by incoming MIDI CCs, drained on the next
+    // `Tick` so `update` stays the single place table edits are applied.
+    pending_remote_actions: Arc<Mutex<VecDeque<RemoteAction>>>,
     // QUAN editing state
     editing_quan: Option<SlotId>,
     quan_input: String,
     // Available loops for dropdown
     available_loops: Vec<(String, PathBuf)>,
+    // Slot under keyboard focus, for arrow-key navigation and row edits that
+    // don't require the mouse. See `select_prev`/`select_next`.
+    selected_slot: Option<SlotId>,
+    // Whether the table auto-scrolls to keep the playing row in view as
+    // playback advances. Toggled off so a user manually browsing isn't
+    // yanked back mid-scroll.
+    auto_follow_scroll: bool,
+    // Last slot the table was auto-scrolled to, so `Message::Tick` only
+    // issues a new `scroll_to` when the playing slot actually changes.
+    last_scrolled_slot: Option<SlotId>,
+    // Which trailing column set the table shows (today's LOOP/LEN/QUAN/QTZ/
+    // NEXT vs. the per-slot performance parameters). See `cycle_column_page`.
+    column_page: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -61,13 +129,34 @@ enum Message {
     Play,
     Stop,
     ToggleClockMode,
+    SetBpm(f32),
+    TapTempo,
     KeyPressed(Key),
     ScreenshotCaptured(Screenshot),
     SetNextSlot(SlotId, Option<SlotId>),
+    BumpNextWeight(SlotId, i32),
+    SetLaunchQuantum(SlotId, LaunchQuantum),
+    RequestSlot(SlotId),
     StartEditQuan(SlotId),
     EditQuanValue(String),
     CommitQuanEdit,
     SetSlotLoop(SlotId, Option<usize>),
+    SelectPrevSlot,
+    SelectNextSlot,
+    CycleSelectedLoop(i32),
+    BumpSelectedQuan(i32),
+    CycleSelectedNext(i32),
+    ToggleAutoFollowScroll,
+    CycleColumnPage(i32),
+    BumpTranspose(SlotId, i32),
+    BumpGain(SlotId, f32),
+    BumpProgramChange(SlotId, i32),
+    ToggleRecordArmed,
+    ToggleArpArmed,
+    ToggleSlotTrack(SlotId),
+    ToggleTrackStep(SlotId, usize),
+    QueueAllLoops,
+    BounceToDisk,
 }
 
 /// Scan for available MIDI loops in the data/out directory.
@@ -100,11 +189,17 @@ fn scan_available_loops() -> Vec<(String, PathBuf)> {
 
 impl Looper {
     fn new() -> Self {
-        let clock_state = ClockState::new();
+        let config = LooperConfig::load(LooperConfig::default_path()).unwrap_or_default();
+        let clock_state = ClockState::with_bandwidth_and_signature(
+            config.clock_bandwidth_hz,
+            config.time_signature,
+        );
         let sequence_player = Arc::new(Mutex::new(SequencePlayer::new()));
         let midi_out = Arc::new(Mutex::new(MidiOut::new().ok()));
         let master_mode = Arc::new(AtomicBool::new(false));
         let screenshot_requested = Arc::new(AtomicBool::new(false));
+        let remote_config = RemoteControlConfig::default();
+        let pending_remote_actions = Arc::new(Mutex::new(VecDeque::new()));
 
         // Scan for available loops
         let available_loops = scan_available_loops();
@@ -149,6 +244,18 @@ impl Looper {
             player.start();
         }
 
+        // Size launch-quantum boundaries to the configured meter rather than
+        // always assuming 4/4.
+        sequence_player
+            .lock()
+            .unwrap()
+            .set_beats_per_bar(config.time_signature.0 as u64);
+
+        sequence_player
+            .lock()
+            .unwrap()
+            .set_swing(config.swing_ratio, config.swing_grid_ticks);
+
         let out_port_name = midi_out
             .lock()
             .unwrap()
@@ -157,6 +264,8 @@ impl Looper {
             .unwrap_or_else(|| "Not connected".to_string());
         let midi_out_connected = midi_out.lock().unwrap().is_some();
 
+        let pad_config = PadGridConfig::default();
+
         // Start MIDI listener with playback callback
         let (midi_in_connection, in_port_name) = start_midi_listener(
             clock_state.clone(),
@@ -164,92 +273,80 @@ impl Looper {
             midi_out.clone(),
             master_mode.clone(),
             screenshot_requested.clone(),
+            pad_config,
+            remote_config.clone(),
+            pending_remote_actions.clone(),
         );
 
-        // Spawn clock generator thread for master mode
-        {
-            let clock_state = clock_state.clone();
-            let sequence_player = sequence_player.clone();
-            let midi_out = midi_out.clone();
-            let master_mode = master_mode.clone();
-
-            std::thread::spawn(move || {
-                use std::time::Instant;
-
-                const BPM: u64 = 120;
-                const CLOCKS_PER_BEAT: u64 = 24;
-                // Nanoseconds per clock = 60_000_000_000 / (BPM * 24)
-                // For 120 BPM: = 60_000_000_000 / 2880 = 20_833_333.333... ns
-                // We calculate target time from clock count to avoid cumulative drift
-
-                let mut clock_count: u64 = 0;
-                let mut start_time = Instant::now();
-                let mut is_running = false;
-
-                loop {
-                    // Only generate clock when in master mode and running
-                    if master_mode.load(Ordering::SeqCst) && clock_state.is_running() {
-                        if !is_running {
-                            println!("Clock generator: starting clocks");
-                            is_running = true;
-                            clock_count = 0;
-                            start_time = Instant::now();
-                        }
+        // Determine the initial master-clock BPM and whether we start in
+        // master mode, from the configured clock source.
+        let master_bpm = match config.clock_source {
+            config::ClockSource::Internal { bpm } => bpm,
+            config::ClockSource::External => midi::DEFAULT_MASTER_BPM,
+        };
+        if matches!(config.clock_source, config::ClockSource::Internal { .. }) {
+            master_mode.store(true, Ordering::SeqCst);
+        }
+        let master_bpm_milli = Arc::new(AtomicU32::new((master_bpm * 1000.0) as u32));
 
-                        // Update internal clock state
-                        clock_state.handle_midi_message(&[midi::MIDI_CLOCK]);
-
-                        // Get events to play at current position
-                        let events = {
-                            let mut player = sequence_player.lock().unwrap();
-                            player.tick(clock_state.get_clock_count())
-                        };
-
-                        // Send clock and events to MIDI output
-                        if let Ok(mut out_guard) = midi_out.lock() {
-                            if let Some(ref mut out) = *out_guard {
-                                // Send clock pulse
-                                if let Err(e) = out.send(&[midi::MIDI_CLOCK]) {
-                                    eprintln!("Failed to send clock: {}", e);
-                                }
-                                // Send note events
-                                for event in &events {
-                                    // Debug: check for unexpected STOP bytes
-                                    if !event.is_empty() && event[0] == midi::MIDI_STOP {
-                                        eprintln!("WARNING: Event contains STOP byte: {:?}", event);
-                                    }
-                                    let _ = out.send(event);
-                                }
-                            }
-                        } else {
-                            eprintln!("Failed to lock midi_out");
-                        }
+        // Spawn the internal clock generator; it only actually produces
+        // clock pulses while `master_mode` is set and the transport active.
+        // It reads `master_bpm_milli` on every tick, so live tempo changes
+        // (BPM controls, tap tempo) take effect immediately.
+        midi::spawn_clock_generator(
+            clock_state.clone(),
+            sequence_player.clone(),
+            midi_out.clone(),
+            master_mode.clone(),
+            master_bpm_milli.clone(),
+        );
 
-                        // Calculate next tick time based on clock count (avoids cumulative drift)
-                        clock_count += 1;
-                        // target_nanos = clock_count * 60_000_000_000 / (BPM * CLOCKS_PER_BEAT)
-                        let target_nanos = (clock_count * 60_000_000_000) / (BPM * CLOCKS_PER_BEAT);
-                        let target_time = start_time + Duration::from_nanos(target_nanos);
+        // Initialize sequence grid (currently empty - will be populated from UI)
+        let sequence_grid = SequenceGrid::new();
 
-                        // Sleep until target time
-                        let now = Instant::now();
-                        if target_time > now {
-                            std::thread::sleep(target_time - now);
-                        }
-                    } else {
-                        // Not running - sleep briefly
-                        if is_running {
-                            println!("Clock generator: stopped");
-                            is_running = false;
-                        }
-                        std::thread::sleep(Duration::from_millis(1));
-                    }
+        // Optionally also bring up a JACK backend for sample-accurate
+        // transport sync, alongside (not instead of) the midir ports above.
+        #[cfg(feature = "jack")]
+        let jack_backend = if matches!(config.midi_backend, config::MidiBackend::Jack) {
+            match jack_backend::JackBackend::new(
+                clock_state.clone(),
+                sequence_player.clone(),
+                master_mode.clone(),
+                screenshot_requested.clone(),
+                pad_config,
+                remote_config.clone(),
+                pending_remote_actions.clone(),
+            ) {
+                Ok(backend) => Some(backend),
+                Err(e) => {
+                    eprintln!("Failed to start JACK backend, using midir only: {}", e);
+                    None
                 }
-            });
+            }
+        } else {
+            None
+        };
+        #[cfg(not(feature = "jack"))]
+        if matches!(config.midi_backend, config::MidiBackend::Jack) {
+            eprintln!(
+                "JACK backend requested but this build doesn't have the `jack` feature enabled; using midir only."
+            );
         }
 
-        // Initialize sequence grid (currently empty - will be populated from UI)
-        let sequence_grid = SequenceGrid::new();
+        let backend_label = {
+            #[cfg(feature = "jack")]
+            {
+                if jack_backend.is_some() {
+                    "midir + JACK".to_string()
+                } else {
+                    "midir".to_string()
+                }
+            }
+            #[cfg(not(feature = "jack"))]
+            {
+                "midir".to_string()
+            }
+        };
 
         Self {
             clock_state,
@@ -259,26 +356,96 @@ impl Looper {
             in_port_name,
             out_port_name,
             master_mode,
+            master_bpm_milli,
+            tap_times: Vec::new(),
+            app_start: Instant::now(),
+            pad_config,
             _midi_in_connection: midi_in_connection,
             midi_out,
+            #[cfg(feature = "jack")]
+            _jack_backend: jack_backend,
+            backend_label,
             sequence_grid,
             screenshot_requested,
+            remote_config,
+            pending_remote_actions,
             editing_quan: None,
             quan_input: String::new(),
             available_loops,
+            selected_slot: Some(SlotId('A')),
+            auto_follow_scroll: true,
+            last_scrolled_slot: None,
+            column_page: 0,
         }
     }
 
     fn update(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::Tick => {
+                // Detect a clock source that stopped sending pulses without
+                // an explicit STOP (unplugged cable, crashed DAW, etc).
+                self.clock_state.check_timeout(std::time::Instant::now());
+
                 // Check for MIDI-triggered screenshot request
                 if self.screenshot_requested.swap(false, Ordering::SeqCst) {
                     return window::oldest().and_then(|window_id| {
                         window::screenshot(window_id)
                     }).map(Message::ScreenshotCaptured);
                 }
+
+                // Apply any table-edit actions a mapped MIDI CC queued since
+                // the last tick (footswitch/hardware controller driving the
+                // sequence table the same way the keyboard does).
+                let remote_actions: Vec<RemoteAction> = {
+                    let mut queue = self.pending_remote_actions.lock().unwrap();
+                    queue.drain(..).collect()
+                };
+                if !remote_actions.is_empty() {
+                    let tasks: Vec<Task<Message>> = remote_actions
+                        .into_iter()
+                        .filter_map(remote_action_to_message)
+                        .map(|message| self.update(message))
+                        .collect();
+                    return Task::batch(tasks);
+                }
+
+                // Mirror grid state back to the pad controller as LED
+                // feedback, so the hardware stays in sync with the screen.
+                let (playback_state, pending_launch) = {
+                    let player = self.sequence_player.lock().unwrap();
+                    (player.grid_playback_state(), player.pending_launch())
+                };
+                let led_messages = self.pad_config.build_led_feedback(
+                    &self.sequence_grid,
+                    playback_state,
+                    pending_launch,
+                    self.launch_blink_on(),
+                );
+                if let Ok(mut out_guard) = self.midi_out.lock() {
+                    if let Some(ref mut out) = *out_guard {
+                        for message in &led_messages {
+                            let _ = out.send(message);
+                        }
+                    }
+                }
                 // State is updated by MIDI thread, just trigger re-render
+
+                // Keep the playing row in view as playback advances, unless
+                // the user turned auto-follow off to browse freely.
+                let current_slot = playback_state.map(|s| s.current_slot);
+                if self.auto_follow_scroll
+                    && current_slot.is_some()
+                    && current_slot != self.last_scrolled_slot
+                {
+                    self.last_scrolled_slot = current_slot;
+                    if let Some(slot_id) = current_slot {
+                        return scrollable::scroll_to(
+                            table_scroll_id(),
+                            scroll_offset_for_slot(slot_id),
+                        )
+                        .discard();
+                    }
+                }
             }
             Message::Play => {
                 let is_master = self.master_mode.load(Ordering::SeqCst);
@@ -291,6 +458,7 @@ impl Looper {
                         if was_running {
                             println!("Sending STOP (restart)");
                             let _ = out.send_stop();
+                            let _ = out.send_all_notes_off();
                         }
                         println!("Sending START");
                         if let Err(e) = out.send_start() {
@@ -309,17 +477,29 @@ impl Looper {
                         self.clock_state.handle_midi_message(&[midi::MIDI_STOP]);
                     }
                     self.clock_state.handle_midi_message(&[midi::MIDI_START]);
-                    self.sequence_player.lock().unwrap().reset();
+                    let stuck_notes = self.sequence_player.lock().unwrap().reset();
+                    if let Ok(mut out_guard) = self.midi_out.lock() {
+                        if let Some(ref mut out) = *out_guard {
+                            for message in &stuck_notes {
+                                let _ = out.send(message);
+                            }
+                        }
+                    }
                 }
             }
             Message::Stop => {
                 let is_master = self.master_mode.load(Ordering::SeqCst);
                 println!("Stop clicked: is_master={}", is_master);
 
+                let stuck_notes = self.sequence_player.lock().unwrap().stop();
                 if let Ok(mut out_guard) = self.midi_out.lock() {
                     if let Some(ref mut out) = *out_guard {
                         println!("Sending STOP");
                         let _ = out.send_stop();
+                        let _ = out.send_all_notes_off();
+                        for message in &stuck_notes {
+                            let _ = out.send(message);
+                        }
                     }
                 }
 
@@ -337,6 +517,36 @@ impl Looper {
                     // Switching to master mode - mark that we've seen transport
                     // so clock pulses from external source don't auto-start
                     self.clock_state.handle_midi_message(&[midi::MIDI_STOP]);
+
+                    // Also claim JACK timebase master, if that backend is up,
+                    // so BBT/tempo get published to the rest of the session.
+                    #[cfg(feature = "jack")]
+                    if let Some(ref backend) = self._jack_backend {
+                        backend.claim_timebase_master(self.clock_state.clone());
+                    }
+                }
+            }
+            Message::SetBpm(bpm) => {
+                let clamped = bpm.clamp(MIN_MASTER_BPM, MAX_MASTER_BPM);
+                self.master_bpm_milli
+                    .store((clamped * 1000.0) as u32, Ordering::SeqCst);
+            }
+            Message::TapTempo => {
+                let now = Instant::now();
+                self.tap_times.retain(|t| now.duration_since(*t) < TAP_TEMPO_WINDOW);
+                self.tap_times.push(now);
+
+                if self.tap_times.len() >= 2 {
+                    let intervals: Vec<f64> = self
+                        .tap_times
+                        .windows(2)
+                        .map(|w| w[1].duration_since(w[0]).as_secs_f64())
+                        .collect();
+                    let avg_secs = intervals.iter().sum::<f64>() / intervals.len() as f64;
+                    if avg_secs > 0.0 {
+                        let bpm = (60.0 / avg_secs) as f32;
+                        return self.update(Message::SetBpm(bpm));
+                    }
                 }
             }
             Message::KeyPressed(key) => {
@@ -346,6 +556,64 @@ impl Looper {
                         window::screenshot(window_id)
                     }).map(Message::ScreenshotCaptured);
                 }
+                // 'T' taps the tempo
+                if key.as_ref() == Key::Character("t") || key.as_ref() == Key::Character("T") {
+                    return self.update(Message::TapTempo);
+                }
+                // Up/Down moves the selected row; Left/Right cycles its
+                // loop; +/- bumps its QUAN; [/] cycles its NEXT pointer --
+                // the sequencer is fully keyboard-operable without these
+                // ever touching the mouse.
+                if key == Key::Named(Named::ArrowUp) {
+                    return self.update(Message::SelectPrevSlot);
+                }
+                if key == Key::Named(Named::ArrowDown) {
+                    return self.update(Message::SelectNextSlot);
+                }
+                if key == Key::Named(Named::ArrowLeft) {
+                    return self.update(Message::CycleSelectedLoop(-1));
+                }
+                if key == Key::Named(Named::ArrowRight) {
+                    return self.update(Message::CycleSelectedLoop(1));
+                }
+                if key.as_ref() == Key::Character("+") || key.as_ref() == Key::Character("=") {
+                    return self.update(Message::BumpSelectedQuan(1));
+                }
+                if key.as_ref() == Key::Character("-") {
+                    return self.update(Message::BumpSelectedQuan(-1));
+                }
+                if key.as_ref() == Key::Character("]") {
+                    return self.update(Message::CycleSelectedNext(1));
+                }
+                if key.as_ref() == Key::Character("[") {
+                    return self.update(Message::CycleSelectedNext(-1));
+                }
+                // 'R' arms/disarms real-time input capture, so the recorder
+                // wired up in `start_midi_listener` (see `record_event`) is
+                // actually reachable without a control surface.
+                if key.as_ref() == Key::Character("r") || key.as_ref() == Key::Character("R") {
+                    return self.update(Message::ToggleRecordArmed);
+                }
+                // 'A' arms/disarms the arpeggiator, so held notes that don't
+                // map to a pad slot get arpeggiated without a control
+                // surface. See `SequencePlayer::arp_note_on`/`arp_note_off`.
+                if key.as_ref() == Key::Character("a") || key.as_ref() == Key::Character("A") {
+                    return self.update(Message::ToggleArpArmed);
+                }
+                // 'Q' queues a fresh arrangement built from every loop in
+                // data/out/ to swap in on the next bar, so
+                // `SequencePlayer::queue`/`QueueQuantum` are reachable
+                // without editing the grid slot by slot.
+                if key.as_ref() == Key::Character("q") || key.as_ref() == Key::Character("Q") {
+                    return self.update(Message::QueueAllLoops);
+                }
+                // 'B' bounces the sequence grid's populated slots to a
+                // Standard MIDI File under bounces/, so `Sequence::render`/
+                // `write_smf` are reachable for turning an arrangement into
+                // a file rather than only existing as library functions.
+                if key.as_ref() == Key::Character("b") || key.as_ref() == Key::Character("B") {
+                    return self.update(Message::BounceToDisk);
+                }
             }
             Message::ScreenshotCaptured(screenshot) => {
                 // Save screenshot to file
@@ -356,6 +624,20 @@ impl Looper {
             Message::SetNextSlot(slot_id, next_slot) => {
                 // Update the grid's NEXT pointer for this slot
                 self.sequence_grid.set_next(slot_id, next_slot);
+                self.sync_grid_to_player();
+            }
+            Message::BumpNextWeight(slot_id, delta) => {
+                self.sequence_grid.bump_next_weight(slot_id, delta);
+                self.sync_grid_to_player();
+            }
+            Message::SetLaunchQuantum(slot_id, quantum) => {
+                self.sequence_grid.set_launch_quantum(slot_id, quantum);
+                self.sync_grid_to_player();
+            }
+            Message::RequestSlot(slot_id) => {
+                // Queue the slot to take over on its next launch-quantum
+                // boundary instead of switching instantly.
+                self.sequence_player.lock().unwrap().request_slot(slot_id);
             }
             Message::StartEditQuan(slot_id) => {
                 // Start editing QUAN for this slot
@@ -376,6 +658,7 @@ impl Looper {
                         // Clamp to valid range (1-999)
                         let count = count.max(1).min(999);
                         self.sequence_grid.set_repeat_count(slot_id, count);
+                        self.sync_grid_to_player();
                     }
                 }
                 self.quan_input.clear();
@@ -402,47 +685,209 @@ impl Looper {
                         self.sequence_grid.clear_loop(slot_id);
                     }
                 }
+                self.sync_grid_to_player();
+            }
+            Message::SelectPrevSlot => {
+                self.selected_slot = Some(select_prev(self.selected_slot));
+            }
+            Message::SelectNextSlot => {
+                self.selected_slot = Some(select_next(self.selected_slot));
+            }
+            Message::CycleSelectedLoop(delta) => {
+                if let Some(slot_id) = self.selected_slot {
+                    let current = self.sequence_grid.get(slot_id).loop_data.as_ref().and_then(|l| {
+                        self.available_loops.iter().position(|(name, _)| name == &l.name)
+                    });
+                    let next = cycle_loop_index(current, self.available_loops.len(), delta);
+                    return self.update(Message::SetSlotLoop(slot_id, next));
+                }
+            }
+            Message::BumpSelectedQuan(delta) => {
+                if let Some(slot_id) = self.selected_slot {
+                    let current = self.sequence_grid.get(slot_id).repeat_count as i32;
+                    let count = (current + delta).clamp(1, 999) as u32;
+                    self.sequence_grid.set_repeat_count(slot_id, count);
+                    self.sync_grid_to_player();
+                }
+            }
+            Message::CycleSelectedNext(delta) => {
+                if let Some(slot_id) = self.selected_slot {
+                    let current = self.sequence_grid.get(slot_id).primary_next_target();
+                    let next = cycle_next_slot(current, delta);
+                    return self.update(Message::SetNextSlot(slot_id, next));
+                }
+            }
+            Message::ToggleAutoFollowScroll => {
+                self.auto_follow_scroll = !self.auto_follow_scroll;
+            }
+            Message::CycleColumnPage(delta) => {
+                self.column_page = cycle_column_page(self.column_page, delta);
+            }
+            Message::BumpTranspose(slot_id, delta) => {
+                let current = self.sequence_grid.get(slot_id).transpose as i32;
+                self.sequence_grid
+                    .set_transpose(slot_id, (current + delta).clamp(-24, 24) as i8);
+                self.sync_grid_to_player();
+            }
+            Message::BumpGain(slot_id, delta) => {
+                let current = self.sequence_grid.get(slot_id).gain_db;
+                self.sequence_grid.set_gain_db(slot_id, current + delta);
+                self.sync_grid_to_player();
+            }
+            Message::BumpProgramChange(slot_id, delta) => {
+                let current = self.sequence_grid.get(slot_id).program_change;
+                let next = match current {
+                    None if delta > 0 => Some(0),
+                    None => None,
+                    Some(pgm) => {
+                        let bumped = pgm as i32 + delta;
+                        if bumped < 0 {
+                            None
+                        } else {
+                            Some(bumped.clamp(0, 127) as u8)
+                        }
+                    }
+                };
+                self.sequence_grid.set_program_change(slot_id, next);
+                self.sync_grid_to_player();
+            }
+            Message::ToggleRecordArmed => {
+                let mut player = self.sequence_player.lock().unwrap();
+                let armed = player.recorder.is_armed();
+                player.recorder.set_armed(!armed);
+            }
+            Message::ToggleArpArmed => {
+                let mut player = self.sequence_player.lock().unwrap();
+                let armed = player.is_arp_armed();
+                player.set_arp_armed(!armed);
+            }
+            Message::ToggleSlotTrack(slot_id) => {
+                if self.sequence_grid.get(slot_id).has_track() {
+                    self.sequence_grid.clear_loop(slot_id);
+                } else {
+                    self.sequence_grid
+                        .load_track(slot_id, Track::new(TimeDivision::Sixteenth, 8, 0));
+                }
+                self.sync_grid_to_player();
+            }
+            Message::ToggleTrackStep(slot_id, step_index) => {
+                let current = self
+                    .sequence_grid
+                    .get(slot_id)
+                    .track_data
+                    .as_ref()
+                    .and_then(|t| t.steps.get(step_index).copied().flatten());
+                let next = if current.is_some() {
+                    None
+                } else {
+                    Some(Step {
+                        note: 60,
+                        velocity: 100,
+                        pitch_bend: 0x2000,
+                        length_step_cents: 85,
+                    })
+                };
+                self.sequence_grid.set_track_step(slot_id, step_index, next);
+                self.sync_grid_to_player();
+            }
+            Message::QueueAllLoops => {
+                // `queue`/`QueueQuantum` only apply to legacy sequence-mode
+                // playback (see `SequencePlayer::tick_loop`'s grid-mode
+                // early return) -- once the grid's been touched there's no
+                // arrangement left to queue into.
+                let mut player = self.sequence_player.lock().unwrap();
+                if player.is_grid_mode() {
+                    eprintln!("Queue All Loops: no-op once the sequence grid is in use");
+                } else {
+                    let mut entries = Vec::new();
+                    for (name, path) in &self.available_loops {
+                        match Loop::from_file(path, 4) {
+                            Ok(mut loaded_loop) => {
+                                loaded_loop.set_channel(0);
+                                entries.push(SequenceEntry {
+                                    loop_data: loaded_loop,
+                                    repeat_count: 2,
+                                });
+                            }
+                            Err(e) => {
+                                eprintln!("Failed to load loop '{}': {}", name, e);
+                            }
+                        }
+                    }
+                    if !entries.is_empty() {
+                        player.queue(Sequence { entries }, QueueQuantum::NextBar);
+                    }
+                }
+            }
+            Message::BounceToDisk => {
+                if let Err(e) = bounce_grid_to_disk(&self.sequence_grid) {
+                    eprintln!("Failed to bounce sequence to disk: {}", e);
+                }
             }
         }
         Task::none()
     }
 
+    /// Whether a queued-launch indicator is currently in the "on" phase of
+    /// its blink cycle. Shared by the on-screen table and pad LED feedback
+    /// so both blink in lockstep.
+    fn launch_blink_on(&self) -> bool {
+        (self.app_start.elapsed().as_millis() / LAUNCH_BLINK_PERIOD.as_millis()) % 2 == 0
+    }
+
+    /// Push the UI-edited sequence grid into the live player, switching it
+    /// into grid-playback mode the first time the grid is touched (until
+    /// then the legacy startup sequence keeps playing).
+    fn sync_grid_to_player(&mut self) {
+        let mut player = self.sequence_player.lock().unwrap();
+        if player.is_grid_mode() {
+            player.update_grid(self.sequence_grid.clone());
+        } else {
+            player.load_grid(self.sequence_grid.clone());
+        }
+    }
+
     fn view(&self) -> Element<'_, Message> {
         let (bar, beat) = self.clock_state.get_position();
         let bpm = self.clock_state.get_bpm();
-        let running = self.clock_state.is_running();
+        let transport = self.clock_state.transport_state();
+        let running = transport == clock::TransportState::Running;
         let is_master = self.master_mode.load(Ordering::SeqCst);
+        let master_bpm = self.master_bpm_milli.load(Ordering::SeqCst) as f32 / 1000.0;
 
         // Button colors based on transport state
-        let (play_color, stop_color) = if running {
-            (
+        let (play_color, stop_color) = match transport {
+            clock::TransportState::Running => (
                 iced::Color::from_rgb(0.2, 0.8, 0.2), // Green when playing
                 iced::Color::from_rgb(0.6, 0.6, 0.6), // Grey
-            )
-        } else {
-            (
+            ),
+            clock::TransportState::Armed => (
+                iced::Color::from_rgb(0.8, 0.8, 0.2), // Yellow while waiting for the first clock
+                iced::Color::from_rgb(0.6, 0.6, 0.6), // Grey
+            ),
+            clock::TransportState::Stopped => (
                 iced::Color::from_rgb(0.6, 0.6, 0.6), // Grey
                 iced::Color::from_rgb(0.8, 0.2, 0.2), // Red when stopped
-            )
+            ),
         };
 
         let in_status = if self.midi_in_connected {
-            format!("IN: {}", self.in_port_name)
+            format!("IN ({}): {}", self.backend_label, self.in_port_name)
         } else {
             "IN: ❌ Not connected".to_string()
         };
 
         let out_status = if self.midi_out_connected {
-            format!("OUT: {}", self.out_port_name)
+            format!("OUT ({}): {}", self.backend_label, self.out_port_name)
         } else {
             "OUT: ❌ Not connected".to_string()
         };
 
         // Clock mode toggle
         let clock_mode_label = if is_master {
-            "Clock: MASTER (120 BPM)"
+            format!("Clock: MASTER ({:.0} BPM)", master_bpm)
         } else {
-            "Clock: EXTERNAL"
+            "Clock: EXTERNAL".to_string()
         };
         let clock_mode_color = if is_master {
             iced::Color::from_rgb(0.8, 0.6, 0.2) // Orange for master
@@ -453,6 +898,20 @@ impl Looper {
             .padding(8)
             .on_press(Message::ToggleClockMode);
 
+        // Master-clock BPM controls: +/- adjustment buttons and a tap-tempo
+        // button (also bound to the 'T' key). Always live so tapping in
+        // EXTERNAL mode primes the tempo for when MASTER mode is enabled.
+        let bpm_controls = row![
+            button(text("-5").size(12)).padding(6).on_press(Message::SetBpm(master_bpm - 5.0)),
+            button(text("-1").size(12)).padding(6).on_press(Message::SetBpm(master_bpm - 1.0)),
+            text(format!("{:.0} BPM", master_bpm)).size(18),
+            button(text("+1").size(12)).padding(6).on_press(Message::SetBpm(master_bpm + 1.0)),
+            button(text("+5").size(12)).padding(6).on_press(Message::SetBpm(master_bpm + 5.0)),
+            button(text("TAP").size(12)).padding(6).on_press(Message::TapTempo),
+        ]
+        .spacing(8)
+        .align_y(Center);
+
         // Get current sequence state
         let (loop_name, loop_progress) = {
             let player = self.sequence_player.lock().unwrap();
@@ -476,11 +935,12 @@ impl Looper {
             .on_press(Message::Stop);
         let transport_controls = row![play_button, stop_button].spacing(20);
 
-        // Get playback state for grid highlighting
-        let playback_state = {
+        // Get playback state and pending launch for grid highlighting
+        let (playback_state, pending_launch) = {
             let player = self.sequence_player.lock().unwrap();
-            player.grid_playback_state()
+            (player.grid_playback_state(), player.pending_launch())
         };
+        let blink_on = self.launch_blink_on();
 
         // Sequence table with QUAN editing state
         let quan_edit = QuanEditState {
@@ -490,21 +950,85 @@ impl Looper {
         let sequence_table: Element<'_, Message> = view_sequence_table(
             &self.sequence_grid,
             playback_state,
+            pending_launch,
+            self.selected_slot,
+            self.column_page,
+            blink_on,
             &self.available_loops,
             quan_edit,
             |slot_id, loop_idx| Message::SetSlotLoop(slot_id, loop_idx),
             |slot_id, next_slot| Message::SetNextSlot(slot_id, next_slot),
-            |slot_id| Message::StartEditQuan(slot_id),
+            |slot_id, delta| Message::BumpNextWeight(slot_id, delta),
+            |slot_id, quantum| Message::SetLaunchQuantum(slot_id, quantum),
+            |slot_id, delta| Message::BumpTranspose(slot_id, delta),
+            |slot_id, delta| Message::BumpGain(slot_id, delta),
+            |slot_id, delta| Message::BumpProgramChange(slot_id, delta),
+            Message::ToggleSlotTrack,
+            Message::ToggleTrackStep,
+            Message::CycleColumnPage,
+            Message::RequestSlot,
+            Message::StartEditQuan,
             Message::EditQuanValue,
             Message::CommitQuanEdit,
         );
 
+        let auto_follow_label = if self.auto_follow_scroll {
+            "Auto-follow: ON"
+        } else {
+            "Auto-follow: OFF"
+        };
+        let auto_follow_button = button(text(auto_follow_label).size(12))
+            .padding(6)
+            .on_press(Message::ToggleAutoFollowScroll);
+
+        let record_armed = self.sequence_player.lock().unwrap().recorder.is_armed();
+        let record_label = if record_armed {
+            "Record: ARMED"
+        } else {
+            "Record: off"
+        };
+        let record_color = if record_armed {
+            iced::Color::from_rgb(0.9, 0.2, 0.2)
+        } else {
+            iced::Color::from_rgb(0.8, 0.8, 0.8)
+        };
+        let record_button = button(text(record_label).size(12).color(record_color))
+            .padding(6)
+            .on_press(Message::ToggleRecordArmed);
+
+        let arp_armed = self.sequence_player.lock().unwrap().is_arp_armed();
+        let arp_label = if arp_armed { "Arp: ARMED" } else { "Arp: off" };
+        let arp_color = if arp_armed {
+            iced::Color::from_rgb(0.9, 0.2, 0.2)
+        } else {
+            iced::Color::from_rgb(0.8, 0.8, 0.8)
+        };
+        let arp_button = button(text(arp_label).size(12).color(arp_color))
+            .padding(6)
+            .on_press(Message::ToggleArpArmed);
+
+        let queue_pending = self.sequence_player.lock().unwrap().queued_sequence_pending();
+        let queue_label = if queue_pending {
+            "Queue All Loops: pending"
+        } else {
+            "Queue All Loops"
+        };
+        let queue_button = button(text(queue_label).size(12))
+            .padding(6)
+            .on_press(Message::QueueAllLoops);
+
+        let bounce_button = button(text("Bounce to Disk").size(12))
+            .padding(6)
+            .on_press(Message::BounceToDisk);
+
         let content = column![
             text("MIDI Looper").size(32),
             text(in_status).size(12),
             text(out_status).size(12),
             clock_mode_button,
             text("").size(5),
+            bpm_controls,
+            text("").size(5),
             row![
                 text(format!("BPM: {:.1}", bpm)).size(24),
                 text(format!("Bar {} · Beat {}", bar, beat)).size(24),
@@ -514,6 +1038,8 @@ impl Looper {
             text("").size(5),
             text(format!("Loop: {} ({})", loop_name, loop_progress)).size(14),
             text("").size(10),
+            row![auto_follow_button, record_button, arp_button, queue_button, bounce_button]
+                .spacing(10),
             sequence_table,
         ]
         .align_x(Center);
@@ -575,12 +1101,84 @@ fn save_screenshot(screenshot: &Screenshot) -> Result<std::path::PathBuf, Box<dy
     Ok(path)
 }
 
+/// Render the sequence grid's populated slots (in A-Z order, skipping empty
+/// ones, one pass at each slot's own `repeat_count`) into a standalone
+/// `Sequence` and bounce it to a Standard MIDI File under `bounces/`,
+/// mirroring `save_screenshot`'s timestamped-filename convention. Without
+/// this, `Sequence::render`/`write_smf` are library functions nobody can
+/// actually invoke from the running app.
+fn bounce_grid_to_disk(grid: &SequenceGrid) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let entries: Vec<SequenceEntry> = SlotId::ALL
+        .iter()
+        .filter_map(|&id| {
+            let slot = grid.get(id);
+            slot.loop_data.clone().map(|loop_data| SequenceEntry {
+                loop_data,
+                repeat_count: slot.repeat_count,
+            })
+        })
+        .collect();
+    if entries.is_empty() {
+        return Err("No loops loaded in the sequence grid to bounce".into());
+    }
+
+    let bar_clocks = BEATS_PER_BAR * CLOCKS_PER_BEAT;
+    let total_clocks: u64 = entries
+        .iter()
+        .map(|e| e.repeat_count.max(1) as u64 * e.loop_data.length_clocks)
+        .sum();
+    let total_bars = ((total_clocks + bar_clocks - 1) / bar_clocks).max(1) as u32;
+    let sequence = Sequence { entries };
+
+    let project_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+    let bounces_dir = project_dir.join("bounces");
+    std::fs::create_dir_all(&bounces_dir)?;
+
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+    let filename = format!("bounce_{}.mid", timestamp);
+    let path = bounces_dir.join(&filename);
+
+    let mut file = std::fs::File::create(&path)?;
+    write_smf(&mut file, sequence.render(total_bars), 480)?;
+
+    println!("Bounced sequence to: {}", path.display());
+    Ok(path)
+}
+
+/// Translate a CC-triggered `RemoteAction` into the `Message` that performs
+/// the same edit the keyboard bindings do. `TriggerScreenshot` isn't
+/// included: it's resolved through the `screenshot_requested` flag instead,
+/// since it needs to build a `window::screenshot` task rather than route
+/// through `update`.
+fn remote_action_to_message(action: RemoteAction) -> Option<Message> {
+    match action {
+        RemoteAction::TriggerScreenshot => None,
+        RemoteAction::SelectPrevSlot => Some(Message::SelectPrevSlot),
+        RemoteAction::SelectNextSlot => Some(Message::SelectNextSlot),
+        RemoteAction::CycleLoopPrev => Some(Message::CycleSelectedLoop(-1)),
+        RemoteAction::CycleLoopNext => Some(Message::CycleSelectedLoop(1)),
+        RemoteAction::BumpQuanDown => Some(Message::BumpSelectedQuan(-1)),
+        RemoteAction::BumpQuanUp => Some(Message::BumpSelectedQuan(1)),
+        RemoteAction::CycleNextPrev => Some(Message::CycleSelectedNext(-1)),
+        RemoteAction::CycleNextNext => Some(Message::CycleSelectedNext(1)),
+        RemoteAction::StartTransport => Some(Message::Play),
+        RemoteAction::StopTransport => Some(Message::Stop),
+        RemoteAction::SelectSlot(slot_id) => Some(Message::RequestSlot(slot_id)),
+        RemoteAction::ToggleRecordArmed => Some(Message::ToggleRecordArmed),
+        RemoteAction::ToggleArpArmed => Some(Message::ToggleArpArmed),
+        RemoteAction::QueueAllLoops => Some(Message::QueueAllLoops),
+    }
+}
+
 fn start_midi_listener(
     clock_state: ClockState,
     sequence_player: Arc<Mutex<SequencePlayer>>,
     midi_out: Arc<Mutex<Option<MidiOut>>>,
     master_mode: Arc<AtomicBool>,
     screenshot_requested: Arc<AtomicBool>,
+    pad_config: PadGridConfig,
+    remote_config: RemoteControlConfig,
+    pending_remote_actions: Arc<Mutex<VecDeque<RemoteAction>>>,
 ) -> (Option<midir::MidiInputConnection<()>>, String) {
     let midi_in = match MidiInput::new("looper-clock") {
         Ok(m) => m,
@@ -606,56 +1204,29 @@ fn start_midi_listener(
     let port = &in_ports[port_idx];
     let port_name = midi_in.port_name(port).unwrap_or_else(|_| "Unknown".into());
 
+    // All the message-handling logic lives in `midi::handle_incoming_midi_message`
+    // so it's identical regardless of which backend delivered the bytes --
+    // this `midir` callback or JACK's MIDI-in port (see
+    // `jack_backend::LooperProcessHandler::process`).
+    let ctx = midi::MidiInputContext {
+        clock_state,
+        sequence_player,
+        midi_out,
+        master_mode,
+        screenshot_requested,
+        pad_config,
+        remote_config,
+        pending_remote_actions,
+    };
+
     let connection = midi_in.connect(
         port,
         "looper-clock-in",
         move |_timestamp, message, _| {
-            // Check for screenshot trigger (CC 119 value 127)
-            if midi::is_screenshot_trigger(message) {
-                screenshot_requested.store(true, Ordering::SeqCst);
-                return;
-            }
-
-            // In master mode, ignore incoming clock and transport - we generate our own
-            if master_mode.load(Ordering::SeqCst) {
-                return;
-            }
-
-            // Update clock state
-            clock_state.handle_midi_message(message);
-
-            // Handle playback on clock ticks
-            if !message.is_empty() && message[0] == midi::MIDI_CLOCK {
-                let clock_count = clock_state.get_clock_count();
-
-                // Get events to play
-                let events = {
-                    let mut player = sequence_player.lock().unwrap();
-                    // Only play when clock is running
-                    if clock_state.is_running() {
-                        player.tick(clock_count)
-                    } else {
-                        Vec::new()
-                    }
-                };
-
-                // Send events to MIDI output
-                if !events.is_empty() {
-                    if let Ok(mut out_guard) = midi_out.lock() {
-                        if let Some(ref mut out) = *out_guard {
-                            for event in events {
-                                let _ = out.send(&event);
-                            }
-                        }
-                    }
-                }
-            }
-
-            // Reset sequence player on transport start
-            if !message.is_empty() && message[0] == midi::MIDI_START {
-                let mut player = sequence_player.lock().unwrap();
-                player.reset();
-            }
+            // midir hands us one already-reassembled message per callback
+            // (it expands running status for us), so the shared pipeline
+            // always sees a full status byte here.
+            midi::handle_incoming_midi_message(&ctx, message);
         },
         (),
     );
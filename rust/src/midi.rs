@@ -3,7 +3,23 @@
 //! This module defines the MIDI real-time messages used for clock synchronization
 //! and transport control. These follow the standard MIDI 1.0 specification.
 
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
 use midir::{MidiOutput, MidiOutputConnection};
+use midly::live::{LiveEvent, SystemCommon, SystemRealtime};
+use midly::MidiMessage;
+
+use crate::clock::ClockState;
+use crate::controller::PadGridConfig;
+use crate::playback::SequencePlayer;
+use crate::remote::{RemoteAction, RemoteControlConfig};
+
+/// Default master-clock tempo if `LooperConfig` doesn't request a specific
+/// internal BPM.
+pub const DEFAULT_MASTER_BPM: f64 = 120.0;
 
 /// MIDI Clock tick - sent 24 times per quarter note (24 ppqn)
 pub const MIDI_CLOCK: u8 = 0xF8;
@@ -17,16 +33,49 @@ pub const MIDI_CONTINUE: u8 = 0xFB;
 /// MIDI Stop - stops playback, maintains current position
 pub const MIDI_STOP: u8 = 0xFC;
 
-/// Number of MIDI clock pulses per quarter note (beat)
+/// Song Position Pointer - two 7-bit data bytes counting 16th-note "MIDI
+/// beats" since the start of the song. Sent by a DAW before `MIDI_CONTINUE`
+/// to resume mid-song.
+pub const MIDI_SPP: u8 = 0xF2;
+
+/// Number of MIDI clock pulses per quarter note. Fixed by the MIDI spec at
+/// 24 ppqn regardless of the configured time signature; BPM is conventionally
+/// expressed in quarter notes per minute, so this is what BPM math uses.
 pub const CLOCKS_PER_BEAT: u64 = 24;
 
-/// Beats per bar (assuming 4/4 time signature)
+/// Beats per bar assumed when `LooperConfig` doesn't specify a time
+/// signature (4/4).
 pub const BEATS_PER_BAR: u64 = 4;
 
+/// Number of MIDI clock pulses in one musical beat of a time signature with
+/// the given denominator (quarter = 24, eighth = 12, sixteenth = 6, ...).
+/// Derived from the fixed 24 ppqn quarter-note resolution: a denominator `d`
+/// beat is `4/d` quarter notes long.
+pub fn clocks_per_beat(denominator: u8) -> u64 {
+    (CLOCKS_PER_BEAT * 4) / denominator.max(1) as u64
+}
+
+/// Convert a decoded Song Position Pointer (in 16th-note "MIDI beats") to a
+/// MIDI clock count (6 clocks per 16th note at 24 ppqn).
+pub fn spp_to_clocks(spp: u16) -> u64 {
+    spp as u64 * (CLOCKS_PER_BEAT / 4)
+}
+
+/// Reconstruct the 14-bit Song Position value from the two 7-bit SPP data
+/// bytes (LSB first, as on the wire).
+pub fn decode_spp(data1: u8, data2: u8) -> u16 {
+    ((data2 as u16) << 7) | (data1 as u16)
+}
+
 /// Wrapper for MIDI output connection.
 pub struct MidiOut {
     connection: MidiOutputConnection,
     pub port_name: String,
+    /// Notes currently sounding (channel, key), tracked from every message
+    /// that passes through `send`/`send_event` so a stuck note can always be
+    /// cleaned up on transport stop, even if it came from recorded loop
+    /// bytes rather than a typed constructor.
+    active_notes: HashSet<(u8, u8)>,
 }
 
 impl MidiOut {
@@ -64,23 +113,399 @@ impl MidiOut {
         Ok(Self {
             connection,
             port_name,
+            active_notes: HashSet::new(),
         })
     }
 
-    /// Send a MIDI message.
+    /// Send a raw MIDI message. Used for event bytes that already come from
+    /// elsewhere as a slice (recorded loop events, passthrough data) where
+    /// there's no richer type to build from.
     pub fn send(&mut self, message: &[u8]) -> Result<(), String> {
+        self.track_note_state(message);
         self.connection
             .send(message)
             .map_err(|e| format!("Failed to send MIDI: {}", e))
     }
 
+    /// Update `active_notes` from an outgoing message, so a Note On/Off that
+    /// came through as raw bytes (recorded loop events) is tracked exactly
+    /// like one sent via `send_event`.
+    fn track_note_state(&mut self, message: &[u8]) {
+        let Ok(event) = LiveEvent::parse(message) else {
+            return;
+        };
+        if let LiveEvent::Midi { channel, message } = event {
+            match message {
+                MidiMessage::NoteOn { key, vel } if vel.as_int() > 0 => {
+                    self.active_notes.insert((channel.as_int(), key.as_int()));
+                }
+                MidiMessage::NoteOn { key, .. } | MidiMessage::NoteOff { key, .. } => {
+                    self.active_notes.remove(&(channel.as_int(), key.as_int()));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Send a Note Off for every note currently tracked as sounding, then
+    /// clear the active set. Call this on transport stop (and before a
+    /// transport restart) so a clip that was cut off mid-note doesn't hang.
+    pub fn send_all_notes_off(&mut self) -> Result<(), String> {
+        let notes: Vec<(u8, u8)> = self.active_notes.drain().collect();
+        for (channel, key) in notes {
+            self.send_event(LiveEvent::Midi {
+                channel: channel.into(),
+                message: MidiMessage::NoteOff {
+                    key: key.into(),
+                    vel: 0.into(),
+                },
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Send a MIDI event built from `midly`'s typed constructors rather than
+    /// a hand-assembled byte array, so a channel/controller/key number can't
+    /// be transposed by a stray array index.
+    ///
+    /// Always encodes with a fresh running-status byte (`&mut None`) instead
+    /// of tracking it across calls: these are one-off, possibly-concurrent
+    /// sends rather than a single serialized stream, so compressing repeated
+    /// status bytes would save nothing and risks desyncing a receiver that
+    /// missed an earlier message.
+    pub fn send_event(&mut self, event: LiveEvent) -> Result<(), String> {
+        let mut buf = Vec::with_capacity(3);
+        event
+            .write(&mut None, &mut buf)
+            .map_err(|e| format!("Failed to encode MIDI event: {}", e))?;
+        self.send(&buf)
+    }
+
     /// Send MIDI Start message.
     pub fn send_start(&mut self) -> Result<(), String> {
-        self.send(&[MIDI_START])
+        self.send_event(LiveEvent::Realtime(SystemRealtime::Start))
     }
 
     /// Send MIDI Stop message.
     pub fn send_stop(&mut self) -> Result<(), String> {
-        self.send(&[MIDI_STOP])
+        self.send_event(LiveEvent::Realtime(SystemRealtime::Stop))
+    }
+
+    /// Send a single MIDI Clock tick.
+    pub fn send_clock(&mut self) -> Result<(), String> {
+        self.send_event(LiveEvent::Realtime(SystemRealtime::TimingClock))
     }
 }
+
+/// Everything the MIDI-in message pipeline needs, bundled so the exact same
+/// handling logic runs regardless of which backend delivered the bytes --
+/// `midir` (see `main::start_midi_listener`) or JACK's MIDI-in port (see
+/// `jack_backend::LooperProcessHandler`). Building this once per listener
+/// and cloning it into each callback avoids re-deriving it per message.
+#[derive(Clone)]
+pub struct MidiInputContext {
+    pub clock_state: ClockState,
+    pub sequence_player: Arc<Mutex<SequencePlayer>>,
+    pub midi_out: Arc<Mutex<Option<MidiOut>>>,
+    pub master_mode: Arc<AtomicBool>,
+    pub screenshot_requested: Arc<AtomicBool>,
+    pub pad_config: PadGridConfig,
+    pub remote_config: RemoteControlConfig,
+    pub pending_remote_actions: Arc<Mutex<VecDeque<RemoteAction>>>,
+}
+
+/// Handle one already-reassembled incoming MIDI message (a full status byte,
+/// as `midir` delivers and as JACK's raw bytes already are -- JACK doesn't
+/// use running status on its event-per-packet port). A message this layer
+/// doesn't understand (malformed bytes, an unsupported meta byte) is simply
+/// ignored rather than propagated as an error.
+pub fn handle_incoming_midi_message(ctx: &MidiInputContext, message: &[u8]) {
+    let event = match LiveEvent::parse(message) {
+        Ok(event) => event,
+        Err(_) => return,
+    };
+
+    // Remote-control CC (screenshot, table navigation/edits, transport),
+    // handled before anything else and regardless of clock mode since these
+    // are UI gestures, not transport messages. See `remote::RemoteControlConfig`.
+    if let LiveEvent::Midi {
+        message: MidiMessage::Controller { controller, value },
+        ..
+    } = event
+    {
+        if let Some(action) = ctx.remote_config.action_for_cc(controller.as_int(), value.as_int())
+        {
+            if action == RemoteAction::TriggerScreenshot {
+                ctx.screenshot_requested.store(true, Ordering::SeqCst);
+            } else {
+                ctx.pending_remote_actions.lock().unwrap().push_back(action);
+            }
+            return;
+        }
+    }
+
+    // Remote-control Note-On (e.g. direct slot-select pads), checked before
+    // the grid-controller pad path below so a configured note range can
+    // drive table actions -- including jumping straight to a slot via
+    // `RemoteAction::SelectSlot` -- the same way remote-control CCs do.
+    if let LiveEvent::Midi {
+        message: MidiMessage::NoteOn { key, vel },
+        ..
+    } = event
+    {
+        if let Some(action) = ctx.remote_config.action_for_note(key.as_int(), vel.as_int()) {
+            if action == RemoteAction::TriggerScreenshot {
+                ctx.screenshot_requested.store(true, Ordering::SeqCst);
+            } else {
+                ctx.pending_remote_actions.lock().unwrap().push_back(action);
+            }
+            return;
+        }
+    }
+
+    // Grid-controller pad hit: arm/launch the mapped slot through the
+    // quantized-launch path. Also handled regardless of clock mode, since
+    // it's a control-surface gesture. Only consumed when the key actually
+    // maps to a pad slot -- an unmapped Note-On (e.g. a musician playing a
+    // melody) falls through to the recording-capture block below instead of
+    // being dropped.
+    if let LiveEvent::Midi {
+        message: MidiMessage::NoteOn { key, vel },
+        ..
+    } = event
+    {
+        if vel.as_int() > 0 {
+            if let Some(slot_id) = ctx.pad_config.note_to_slot(key.as_int()) {
+                ctx.sequence_player.lock().unwrap().request_slot(slot_id);
+                return;
+            }
+        }
+    }
+
+    // Real-time input capture for recording: while armed, any other
+    // channel-voice message (CC, aftertouch, pitch bend, or an unmapped
+    // note -- a mapped pad note-on is claimed above) is timestamped with the
+    // current clock position and handed to the player, which quantizes and
+    // phase-locks it to whatever loop is currently playing. `record_event`
+    // itself no-ops when not armed.
+    //
+    // The same unmapped note also feeds the arpeggiator's held chord (see
+    // `SequencePlayer::arp_note_on`/`arp_note_off`) when arp mode is armed,
+    // independent of whether recording is also armed -- a performer can
+    // arp and record at once if they want the pattern baked into a loop.
+    if let LiveEvent::Midi { channel, message: midi_message } = event {
+        let clock_count = ctx.clock_state.get_clock_count();
+        match midi_message {
+            MidiMessage::NoteOn { key, vel } if vel.as_int() > 0 => {
+                ctx.sequence_player
+                    .lock()
+                    .unwrap()
+                    .arp_note_on(key.as_int(), clock_count);
+            }
+            MidiMessage::NoteOn { key, .. } | MidiMessage::NoteOff { key, .. } => {
+                ctx.sequence_player
+                    .lock()
+                    .unwrap()
+                    .arp_note_off(key.as_int(), clock_count);
+            }
+            _ => {}
+        }
+
+        let mut bytes = Vec::with_capacity(3);
+        if (LiveEvent::Midi { channel, message: midi_message })
+            .write(&mut None, &mut bytes)
+            .is_ok()
+        {
+            ctx.sequence_player
+                .lock()
+                .unwrap()
+                .record_event(channel.as_int(), bytes, clock_count);
+        }
+    }
+
+    // Everything from here on is transport: clock, start/continue/stop, and
+    // Song Position. Note-off, aftertouch, other CCs, SysEx, and Active
+    // Sensing fall through and are ignored.
+    let is_transport = matches!(
+        event,
+        LiveEvent::Realtime(
+            SystemRealtime::TimingClock
+                | SystemRealtime::Start
+                | SystemRealtime::Continue
+                | SystemRealtime::Stop
+        ) | LiveEvent::Common(SystemCommon::SongPosition(_))
+    );
+    if !is_transport {
+        return;
+    }
+
+    // In master mode, ignore incoming clock and transport - we generate our own
+    if ctx.master_mode.load(Ordering::SeqCst) {
+        return;
+    }
+
+    // Update clock state. `ClockState` still parses the raw bytes itself
+    // (it also replays timestamped messages in tests), so hand it the
+    // original slice rather than re-encoding the typed event we just
+    // matched on.
+    ctx.clock_state.handle_midi_message(message);
+
+    // Handle playback on clock ticks
+    if matches!(event, LiveEvent::Realtime(SystemRealtime::TimingClock)) {
+        let clock_count = ctx.clock_state.get_clock_count();
+
+        // Get events to play
+        let events = {
+            let mut player = ctx.sequence_player.lock().unwrap();
+            // Only play when clock is running
+            if ctx.clock_state.is_running() {
+                player.tick(clock_count)
+            } else {
+                Vec::new()
+            }
+        };
+
+        // Send events to MIDI output
+        if !events.is_empty() {
+            if let Ok(mut out_guard) = ctx.midi_out.lock() {
+                if let Some(ref mut out) = *out_guard {
+                    for event in events {
+                        let _ = out.send(&event);
+                    }
+                }
+            }
+        }
+    }
+
+    // Reset sequence player on transport start. Flush any notes still
+    // sounding first, in case the reset interrupts a clip mid-note.
+    if matches!(event, LiveEvent::Realtime(SystemRealtime::Start)) {
+        let stuck_notes = {
+            let mut player = ctx.sequence_player.lock().unwrap();
+            player.reset()
+        };
+        if let Ok(mut out_guard) = ctx.midi_out.lock() {
+            if let Some(ref mut out) = *out_guard {
+                let _ = out.send_all_notes_off();
+                for message in &stuck_notes {
+                    let _ = out.send(message);
+                }
+            }
+        }
+    }
+
+    // Unlike Start, Continue resumes from wherever playback left off rather
+    // than rewinding: `clock_state` already keeps `clock_count` (and
+    // `loop_start_clock`, untouched here) as they were before the Stop this
+    // Continue follows, so there's nothing for the player to do but keep
+    // ticking from there -- no separate Continue handling needed.
+
+    // Flush any notes left sounding when the host stops the transport, so a
+    // clip cut off mid-note doesn't hang forever.
+    if matches!(event, LiveEvent::Realtime(SystemRealtime::Stop)) {
+        if let Ok(mut out_guard) = ctx.midi_out.lock() {
+            if let Some(ref mut out) = *out_guard {
+                let _ = out.send_all_notes_off();
+            }
+        }
+    }
+}
+
+/// Spawn the internal clock-generator thread that lets the looper act as
+/// MIDI clock master instead of only following an external clock.
+///
+/// While `master_mode` is set and the transport is active, this sleeps the
+/// per-clock interval (`60.0 / (bpm * 24.0)` seconds), computing each tick's
+/// target `Instant` from a schedule origin rather than accumulating sleep
+/// error so the generated clock doesn't drift. Each tick is fed into
+/// `clock_state` (so position/BPM display work identically to following an
+/// external clock) and used to drive `sequence_player`, with both the clock
+/// pulse and any resulting note events sent out through `midi_out`.
+///
+/// `bpm_milli` holds the live tempo in milli-BPM (e.g. `120_000` for 120
+/// BPM) and is read on every iteration, so tap tempo or a BPM control in the
+/// UI take effect immediately. Changing it re-anchors the drift-correction
+/// schedule from the current tick rather than jumping the whole generated
+/// stream to match the new rate retroactively.
+pub fn spawn_clock_generator(
+    clock_state: ClockState,
+    sequence_player: Arc<Mutex<SequencePlayer>>,
+    midi_out: Arc<Mutex<Option<MidiOut>>>,
+    master_mode: Arc<AtomicBool>,
+    bpm_milli: Arc<AtomicU32>,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        let mut clock_count: u64 = 0;
+        let mut generating = false;
+
+        // The clock at which the current tempo took effect, and its value,
+        // so a live BPM change only affects ticks from here forward.
+        let mut schedule_origin_clock: u64 = 0;
+        let mut schedule_start = Instant::now();
+        let mut schedule_bpm_milli = bpm_milli.load(Ordering::SeqCst);
+
+        loop {
+            if master_mode.load(Ordering::SeqCst) && clock_state.is_active() {
+                if !generating {
+                    generating = true;
+                    clock_count = 0;
+                    schedule_origin_clock = 0;
+                    schedule_start = Instant::now();
+                    schedule_bpm_milli = bpm_milli.load(Ordering::SeqCst);
+                }
+
+                clock_state.handle_midi_message(&[MIDI_CLOCK]);
+
+                let events = {
+                    let mut player = sequence_player.lock().unwrap();
+                    player.tick(clock_state.get_clock_count())
+                };
+
+                if let Ok(mut out_guard) = midi_out.lock() {
+                    if let Some(ref mut out) = *out_guard {
+                        let _ = out.send_clock();
+                        for event in &events {
+                            let _ = out.send(event);
+                        }
+                    }
+                }
+
+                clock_count += 1;
+
+                let current_bpm_milli = bpm_milli.load(Ordering::SeqCst);
+                if current_bpm_milli != schedule_bpm_milli {
+                    // Tempo changed: re-anchor so only ticks from here use
+                    // the new rate instead of reinterpreting history.
+                    schedule_bpm_milli = current_bpm_milli;
+                    schedule_origin_clock = clock_count - 1;
+                    schedule_start = Instant::now();
+                }
+
+                let bpm = schedule_bpm_milli as f64 / 1000.0;
+                let seconds_per_clock = 60.0 / (bpm * CLOCKS_PER_BEAT as f64);
+                let ticks_since_origin = clock_count - schedule_origin_clock;
+                let target =
+                    schedule_start + Duration::from_secs_f64(ticks_since_origin as f64 * seconds_per_clock);
+
+                let now = Instant::now();
+                if target > now {
+                    std::thread::sleep(target - now);
+                }
+            } else {
+                if generating {
+                    // Transport just stopped generating (master mode turned
+                    // off, or the transport itself stopped): flush any
+                    // notes left sounding so a clip cut off mid-note
+                    // doesn't hang.
+                    if let Ok(mut out_guard) = midi_out.lock() {
+                        if let Some(ref mut out) = *out_guard {
+                            let _ = out.send_all_notes_off();
+                        }
+                    }
+                }
+                generating = false;
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        }
+    })
+}
@@ -4,10 +4,13 @@
 //! looping continuously.
 
 use midly::{MidiMessage, Smf, TrackEventKind};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::fs;
 use std::path::Path;
 
-use crate::midi::CLOCKS_PER_BEAT;
+use crate::arp::{ArpPattern, Arpeggiator};
+use crate::midi::{BEATS_PER_BAR, CLOCKS_PER_BEAT};
 
 /// A single MIDI event to be played.
 #[derive(Debug, Clone)]
@@ -32,10 +35,30 @@ pub struct Loop {
 }
 
 impl Loop {
-    /// Load a MIDI file and convert it to a Loop.
+    /// Load a Standard MIDI File and convert it to a `Loop` ready to drive
+    /// `SequencePlayer::tick`.
     ///
     /// The `loop_length_bars` parameter specifies how many bars the loop should be.
-    /// Events are quantized to 24 ppqn (MIDI clock resolution).
+    /// Events are re-quantized from the file's own tick resolution (PPQ, from
+    /// the header's `Timing::Metrical`) onto the fixed 24-ppqn MIDI clock
+    /// grid the playback callback runs on: `clock_tick = round(event_ticks *
+    /// 24 / ppq)`.
+    ///
+    /// Both Format 0 (single track) and Format 1 (multiple simultaneous
+    /// tracks) files work the same way here: every track's delta-times
+    /// accumulate independently from tick 0, and the resulting events are
+    /// merged by sorting on `clock_position` once all tracks are flattened
+    /// -- which is exactly what "simultaneous tracks" means for Format 1,
+    /// and a no-op for Format 0's single track.
+    ///
+    /// Tempo meta events are ignored: this mapping works in ticks, not real
+    /// time, so a file's relative event placement survives a tempo change
+    /// without needing to track one -- we only need wall-clock tempo when
+    /// generating our own MIDI clock, which `ClockState`/`spawn_clock_generator`
+    /// already handle separately from loop content.
+    ///
+    /// SysEx events pass through unchanged (rebuilt with their `0xF0` status
+    /// byte restored) rather than being dropped.
     pub fn from_file<P: AsRef<Path>>(path: P, loop_length_bars: u64) -> Result<Self, String> {
         let path = path.as_ref();
         let name = path
@@ -64,45 +87,67 @@ impl Loop {
             for event in track.iter() {
                 tick += event.delta.as_int() as u64;
 
-                if let TrackEventKind::Midi { channel, message } = event.kind {
-                    // Convert file ticks to 24 ppqn clock ticks
-                    let clock_position = (tick * CLOCKS_PER_BEAT) / file_ppq;
-
-                    // Build the raw MIDI message
-                    let msg_bytes = match message {
-                        MidiMessage::NoteOn { key, vel } => {
-                            vec![0x90 | channel.as_int(), key.as_int(), vel.as_int()]
-                        }
-                        MidiMessage::NoteOff { key, vel } => {
-                            vec![0x80 | channel.as_int(), key.as_int(), vel.as_int()]
-                        }
-                        MidiMessage::Aftertouch { key, vel } => {
-                            vec![0xA0 | channel.as_int(), key.as_int(), vel.as_int()]
-                        }
-                        MidiMessage::Controller { controller, value } => {
-                            vec![0xB0 | channel.as_int(), controller.as_int(), value.as_int()]
-                        }
-                        MidiMessage::ProgramChange { program } => {
-                            vec![0xC0 | channel.as_int(), program.as_int()]
-                        }
-                        MidiMessage::ChannelAftertouch { vel } => {
-                            vec![0xD0 | channel.as_int(), vel.as_int()]
-                        }
-                        MidiMessage::PitchBend { bend } => {
-                            let value = bend.as_int() as u16;
-                            vec![
-                                0xE0 | channel.as_int(),
-                                (value & 0x7F) as u8,
-                                ((value >> 7) & 0x7F) as u8,
-                            ]
-                        }
-                    };
-
-                    events.push(LoopEvent {
-                        clock_position,
-                        channel: channel.as_int(),
-                        message: msg_bytes,
-                    });
+                // Convert file ticks to 24 ppqn clock ticks, rounding to the
+                // nearest clock (round-half-up) rather than truncating, so a
+                // note doesn't drift early by up to a whole clock tick:
+                // round(tick * 24 / ppq).
+                let clock_position = (tick * CLOCKS_PER_BEAT + file_ppq / 2) / file_ppq;
+
+                match event.kind {
+                    TrackEventKind::Midi { channel, message } => {
+                        // Build the raw MIDI message
+                        let msg_bytes = match message {
+                            MidiMessage::NoteOn { key, vel } => {
+                                vec![0x90 | channel.as_int(), key.as_int(), vel.as_int()]
+                            }
+                            MidiMessage::NoteOff { key, vel } => {
+                                vec![0x80 | channel.as_int(), key.as_int(), vel.as_int()]
+                            }
+                            MidiMessage::Aftertouch { key, vel } => {
+                                vec![0xA0 | channel.as_int(), key.as_int(), vel.as_int()]
+                            }
+                            MidiMessage::Controller { controller, value } => {
+                                vec![0xB0 | channel.as_int(), controller.as_int(), value.as_int()]
+                            }
+                            MidiMessage::ProgramChange { program } => {
+                                vec![0xC0 | channel.as_int(), program.as_int()]
+                            }
+                            MidiMessage::ChannelAftertouch { vel } => {
+                                vec![0xD0 | channel.as_int(), vel.as_int()]
+                            }
+                            MidiMessage::PitchBend { bend } => {
+                                let value = bend.as_int() as u16;
+                                vec![
+                                    0xE0 | channel.as_int(),
+                                    (value & 0x7F) as u8,
+                                    ((value >> 7) & 0x7F) as u8,
+                                ]
+                            }
+                        };
+
+                        events.push(LoopEvent {
+                            clock_position,
+                            channel: channel.as_int(),
+                            message: msg_bytes,
+                        });
+                    }
+                    TrackEventKind::SysEx(data) => {
+                        // Passed through unchanged rather than dropped:
+                        // rebuild the wire bytes with the leading status
+                        // byte the file doesn't store. `channel` has no
+                        // meaning for SysEx; 0 is just a placeholder, same
+                        // as the rest of this struct treats "not applicable".
+                        let mut message = Vec::with_capacity(data.len() + 1);
+                        message.push(0xF0);
+                        message.extend_from_slice(data);
+
+                        events.push(LoopEvent {
+                            clock_position,
+                            channel: 0,
+                            message,
+                        });
+                    }
+                    _ => {}
                 }
             }
         }
@@ -133,6 +178,137 @@ impl Loop {
     }
 }
 
+// ============ Step-based Tracks ============
+
+/// Musical time division of a single step, on the fixed 24 ppqn MIDI clock
+/// grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeDivision {
+    ThirtySecond,
+    Sixteenth,
+    Eighth,
+    Quarter,
+    Whole,
+}
+
+impl TimeDivision {
+    /// MIDI clocks spanned by one step of this division (24 ppqn).
+    pub fn clocks_per_step(self) -> u64 {
+        match self {
+            TimeDivision::ThirtySecond => 3,
+            TimeDivision::Sixteenth => 6,
+            TimeDivision::Eighth => 12,
+            TimeDivision::Quarter => 24,
+            TimeDivision::Whole => 96,
+        }
+    }
+}
+
+/// A single programmed step in a `Track`.
+#[derive(Debug, Clone, Copy)]
+pub struct Step {
+    pub note: u8,
+    pub velocity: u8,
+    /// Pitch bend for the step (14-bit, 8192 = center/no bend).
+    pub pitch_bend: u16,
+    /// Gate length as a percentage (1-100) of the step's clock span. The
+    /// note-off fires this far into the step rather than at the next step
+    /// boundary, so e.g. 85 leaves a short silence before a retrigger.
+    pub length_step_cents: u8,
+}
+
+/// A programmable step sequencer track: a fixed grid of steps played on a
+/// loop, as an alternative note source to a file-backed `Loop`.
+#[derive(Debug, Clone)]
+pub struct Track {
+    pub time_division: TimeDivision,
+    /// Number of steps in the track.
+    pub length: usize,
+    pub midi_channel: u8,
+    pub steps: Vec<Option<Step>>,
+}
+
+impl Track {
+    /// Create an empty track of `length` steps.
+    pub fn new(time_division: TimeDivision, length: usize, midi_channel: u8) -> Self {
+        Self {
+            time_division,
+            length,
+            midi_channel,
+            steps: vec![None; length],
+        }
+    }
+
+    /// Render this track to a `Loop` of note-on/off `LoopEvent`s, so it plays
+    /// back through the same clock-driven engine as a file-backed loop.
+    ///
+    /// Each step's note-off is scheduled `length_step_cents`% into its clock
+    /// span rather than at the next step boundary. A step that retriggers a
+    /// note already sustaining from an earlier step cancels that note's
+    /// pending note-off first, so tied/overlapping steps never leave a note
+    /// hanging.
+    pub fn to_loop(&self, name: &str) -> Loop {
+        let clocks_per_step = self.time_division.clocks_per_step();
+        let length_clocks = self.length as u64 * clocks_per_step;
+        let status_on = 0x90 | (self.midi_channel & 0x0F);
+        let status_off = 0x80 | (self.midi_channel & 0x0F);
+
+        let mut events: Vec<LoopEvent> = Vec::new();
+        // Pending note-off clock position per note, so a retriggered note
+        // cancels its predecessor's note-off instead of both firing.
+        let mut pending_off: HashMap<u8, u64> = HashMap::new();
+
+        for (i, step) in self.steps.iter().enumerate() {
+            let Some(step) = step else { continue };
+            let step_start = i as u64 * clocks_per_step;
+
+            if let Some(off_position) = pending_off.remove(&step.note) {
+                if off_position >= step_start {
+                    events.retain(|e| {
+                        !(e.clock_position == off_position
+                            && e.message.as_slice() == [status_off, step.note, 0])
+                    });
+                }
+            }
+
+            if step.pitch_bend != 0x2000 {
+                events.push(LoopEvent {
+                    clock_position: step_start,
+                    channel: self.midi_channel,
+                    message: vec![
+                        0xE0 | (self.midi_channel & 0x0F),
+                        (step.pitch_bend & 0x7F) as u8,
+                        ((step.pitch_bend >> 7) & 0x7F) as u8,
+                    ],
+                });
+            }
+
+            let gate_clocks = (clocks_per_step * step.length_step_cents.min(100) as u64) / 100;
+            let off_position = (step_start + gate_clocks.max(1)).min(length_clocks.saturating_sub(1));
+
+            events.push(LoopEvent {
+                clock_position: step_start,
+                channel: self.midi_channel,
+                message: vec![status_on, step.note, step.velocity],
+            });
+            events.push(LoopEvent {
+                clock_position: off_position,
+                channel: self.midi_channel,
+                message: vec![status_off, step.note, 0],
+            });
+            pending_off.insert(step.note, off_position);
+        }
+
+        events.sort_by_key(|e| e.clock_position);
+
+        Loop {
+            name: name.to_string(),
+            length_clocks,
+            events,
+        }
+    }
+}
+
 // ============ Slot-based Sequence Grid ============
 
 /// A slot identifier (A-Z) for the sequence grid.
@@ -171,6 +347,139 @@ impl std::fmt::Display for SlotId {
     }
 }
 
+/// Ableton-style launch-quantization boundary: a slot requested via
+/// `SequencePlayer::request_slot` doesn't switch instantly, it waits for the
+/// next MIDI clock that is a multiple of this many clocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LaunchQuantum {
+    OneBeat,
+    OneBar,
+    TwoBars,
+    FourBars,
+}
+
+impl LaunchQuantum {
+    /// All quantum choices, for UI pickers.
+    pub const ALL: [LaunchQuantum; 4] = [
+        LaunchQuantum::OneBeat,
+        LaunchQuantum::OneBar,
+        LaunchQuantum::TwoBars,
+        LaunchQuantum::FourBars,
+    ];
+
+    /// Number of MIDI clocks in this quantum, given the song's beats-per-bar.
+    pub fn clocks(self, beats_per_bar: u64) -> u64 {
+        let bar_clocks = beats_per_bar.max(1) * CLOCKS_PER_BEAT;
+        match self {
+            LaunchQuantum::OneBeat => CLOCKS_PER_BEAT,
+            LaunchQuantum::OneBar => bar_clocks,
+            LaunchQuantum::TwoBars => bar_clocks * 2,
+            LaunchQuantum::FourBars => bar_clocks * 4,
+        }
+    }
+}
+
+impl Default for LaunchQuantum {
+    fn default() -> Self {
+        LaunchQuantum::OneBar
+    }
+}
+
+impl fmt::Display for LaunchQuantum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            LaunchQuantum::OneBeat => "1 beat",
+            LaunchQuantum::OneBar => "1 bar",
+            LaunchQuantum::TwoBars => "2 bars",
+            LaunchQuantum::FourBars => "4 bars",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// Captures real-time MIDI input for recording into a `Loop`, with optional
+/// quantization and overdub.
+///
+/// Recorded positions are always phase-relative to the loop currently
+/// playing (`SequencePlayer`'s own `loop_start_clock`/`length_clocks`
+/// bookkeeping), so the record head stays locked to the loop length for
+/// free on `MIDI_START` -- there's nothing here that needs its own reset
+/// hook beyond discarding whatever hadn't been committed yet.
+#[derive(Debug, Clone, Default)]
+pub struct LoopRecorder {
+    armed: bool,
+    /// Merge newly captured events with whatever a slot's loop already has,
+    /// instead of replacing it outright.
+    overdub: bool,
+    /// Snap each captured event's position to the nearest multiple of this
+    /// many clock ticks. 0 disables quantization.
+    quantize_ticks: u64,
+    captured: Vec<LoopEvent>,
+}
+
+impl LoopRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_armed(&self) -> bool {
+        self.armed
+    }
+
+    pub fn set_armed(&mut self, armed: bool) {
+        self.armed = armed;
+    }
+
+    pub fn set_overdub(&mut self, overdub: bool) {
+        self.overdub = overdub;
+    }
+
+    /// Set the quantization grid in clock ticks (e.g. 6 = nearest
+    /// sixteenth note at 24 ppqn). 0 disables quantization.
+    pub fn set_quantize_ticks(&mut self, ticks: u64) {
+        self.quantize_ticks = ticks;
+    }
+
+    /// Capture one incoming event at `position_in_loop` (already phase-
+    /// relative to the loop start), snapping it to the quantize grid if
+    /// set. No-op while not armed.
+    fn capture(&mut self, channel: u8, message: Vec<u8>, position_in_loop: u64) {
+        if !self.armed {
+            return;
+        }
+        let clock_position = if self.quantize_ticks > 0 {
+            let q = self.quantize_ticks;
+            ((position_in_loop + q / 2) / q) * q
+        } else {
+            position_in_loop
+        };
+        self.captured.push(LoopEvent {
+            clock_position,
+            channel,
+            message,
+        });
+    }
+
+    /// Commit everything captured so far into `target`, replacing its
+    /// events outright unless overdub mode merges them in alongside what's
+    /// already there.
+    fn commit(&mut self, target: &mut Loop) {
+        let mut new_events: Vec<LoopEvent> = self.captured.drain(..).collect();
+        if self.overdub {
+            target.events.append(&mut new_events);
+        } else {
+            target.events = new_events;
+        }
+        target.events.sort_by_key(|e| e.clock_position);
+    }
+}
+
+/// Default weight assigned to a NEXT target when none was specified yet
+/// (e.g. the first time the pick_list sets a primary target). Chosen so a
+/// lone target always wins its roll regardless of what it's compared
+/// against, matching a plain `Option<SlotId>`'s old deterministic behavior.
+const DEFAULT_NEXT_WEIGHT: u8 = 100;
+
 /// A single slot in the sequence grid.
 #[derive(Debug, Clone)]
 pub struct SequenceSlot {
@@ -178,10 +487,35 @@ pub struct SequenceSlot {
     pub id: SlotId,
     /// Optional loaded loop
     pub loop_data: Option<Loop>,
+    /// Optional step-based track authoring this slot's `loop_data`. When
+    /// set, `loop_data` is always the `Track::to_loop` rendering of this --
+    /// edits go through `SequenceGrid::load_track`/`set_track_step`, which
+    /// re-render `loop_data` afterward, so playback keeps reading the same
+    /// `Loop`-based engine regardless of which source authored it.
+    pub track_data: Option<Track>,
     /// Repeat count before advancing to next slot
     pub repeat_count: u32,
-    /// Next slot to play (None = stop playback)
-    pub next_slot: Option<SlotId>,
+    /// Weighted set of slots this one may transition to when its repeat
+    /// count is exhausted: `(target, weight 0-100)`. Empty means stop
+    /// playback. A single entry behaves exactly like the old deterministic
+    /// `next_slot: Option<SlotId>` regardless of its weight. Multiple
+    /// entries branch randomly in proportion to their weights -- see
+    /// `SequencePlayer::choose_weighted_target`.
+    pub next_targets: Vec<(SlotId, u8)>,
+    /// Clip-launch quantization boundary for this slot (1 beat/bar/2/4 bars).
+    pub launch_quantum: LaunchQuantum,
+    /// Semitones to transpose this slot's note events by. Applied live as
+    /// each event is collected for send (see `collect_grid_events_at_position`)
+    /// rather than baked into `loop_data`, so a performer can detune a loop
+    /// without re-rendering it.
+    pub transpose: i8,
+    /// Gain adjustment in dB applied live to this slot's note-on velocities
+    /// as each event is collected for send. 0.0 leaves velocities untouched.
+    pub gain_db: f32,
+    /// Optional MIDI Program Change fired once when this slot becomes
+    /// current (see `program_change_event`), for switching the receiving
+    /// instrument's patch alongside the loop.
+    pub program_change: Option<u8>,
 }
 
 impl SequenceSlot {
@@ -190,11 +524,31 @@ impl SequenceSlot {
         Self {
             id,
             loop_data: None,
+            track_data: None,
             repeat_count: 1,
-            next_slot: None,
+            next_targets: Vec::new(),
+            launch_quantum: LaunchQuantum::default(),
+            transpose: 0,
+            gain_db: 0.0,
+            program_change: None,
         }
     }
 
+    /// The slot's primary NEXT target (first in `next_targets`), for the
+    /// `NextSlotOption` pick_list which only ever edits that one entry.
+    pub fn primary_next_target(&self) -> Option<SlotId> {
+        self.next_targets.first().map(|&(slot, _)| slot)
+    }
+
+    /// Weight of the primary NEXT target, for the table's "P" weight
+    /// sub-cell. Defaults to `DEFAULT_NEXT_WEIGHT` if there's no target yet.
+    pub fn primary_next_weight(&self) -> u8 {
+        self.next_targets
+            .first()
+            .map(|&(_, weight)| weight)
+            .unwrap_or(DEFAULT_NEXT_WEIGHT)
+    }
+
     /// Get loop name or "--" for empty slots.
     pub fn loop_name(&self) -> &str {
         self.loop_data
@@ -218,6 +572,12 @@ impl SequenceSlot {
     pub fn has_loop(&self) -> bool {
         self.loop_data.is_some()
     }
+
+    /// Check if this slot's `loop_data` is authored from a step `Track`
+    /// rather than a file-backed `Loop`.
+    pub fn has_track(&self) -> bool {
+        self.track_data.is_some()
+    }
 }
 
 /// Grid of 26 sequence slots (A-Z) with playback configuration.
@@ -256,20 +616,98 @@ impl SequenceGrid {
         self.slots[id.index()].loop_data = Some(loop_data);
     }
 
-    /// Clear a slot's loop.
+    /// Clear a slot's loop, and any track authoring it.
     pub fn clear_loop(&mut self, id: SlotId) {
         self.slots[id.index()].loop_data = None;
+        self.slots[id.index()].track_data = None;
+    }
+
+    /// Load a step `Track` into a slot as an alternative to a file-backed
+    /// `Loop`: stores `track` and immediately renders it into `loop_data`
+    /// via `Track::to_loop`, so it plays back through the same clock-driven
+    /// engine as any other loop.
+    pub fn load_track(&mut self, id: SlotId, track: Track) {
+        let slot = &mut self.slots[id.index()];
+        slot.loop_data = Some(track.to_loop(&format!("Track {}", id)));
+        slot.track_data = Some(track);
+    }
+
+    /// Set (or clear) a single step of a slot's track and re-render
+    /// `loop_data` to match. No-op if the slot has no track loaded or
+    /// `step_index` is out of range.
+    pub fn set_track_step(&mut self, id: SlotId, step_index: usize, step: Option<Step>) {
+        let slot = &mut self.slots[id.index()];
+        let Some(track) = slot.track_data.as_mut() else {
+            return;
+        };
+        let Some(slot_step) = track.steps.get_mut(step_index) else {
+            return;
+        };
+        *slot_step = step;
+        slot.loop_data = Some(track.to_loop(&format!("Track {}", id)));
     }
 
-    /// Set the NEXT pointer for a slot.
+    /// Set the primary NEXT target for a slot (the one the `NextSlotOption`
+    /// pick_list edits). Preserves that target's existing weight if it's
+    /// already the primary; any other (programmatically-added) targets are
+    /// left alone. `None` clears all targets, stopping playback there.
     pub fn set_next(&mut self, id: SlotId, next: Option<SlotId>) {
-        self.slots[id.index()].next_slot = next;
+        let slot = &mut self.slots[id.index()];
+        match next {
+            None => slot.next_targets.clear(),
+            Some(target) => {
+                let weight = slot.primary_next_weight();
+                if slot.next_targets.is_empty() {
+                    slot.next_targets.push((target, weight));
+                } else {
+                    slot.next_targets[0] = (target, weight);
+                }
+            }
+        }
+    }
+
+    /// Adjust the primary NEXT target's weight by `delta`, clamped to
+    /// 0-100. No-op if the slot has no primary target yet.
+    pub fn bump_next_weight(&mut self, id: SlotId, delta: i32) {
+        let slot = &mut self.slots[id.index()];
+        if let Some(entry) = slot.next_targets.first_mut() {
+            let current = entry.1 as i32;
+            entry.1 = (current + delta).clamp(0, 100) as u8;
+        }
+    }
+
+    /// Replace a slot's full set of weighted NEXT targets, for generative
+    /// setups that branch to more than one target. The table UI only edits
+    /// `next_targets[0]` through `set_next`/`bump_next_weight`; this is the
+    /// entry point for configuring additional targets.
+    pub fn set_next_targets(&mut self, id: SlotId, targets: Vec<(SlotId, u8)>) {
+        self.slots[id.index()].next_targets = targets;
     }
 
     /// Set repeat count for a slot.
     pub fn set_repeat_count(&mut self, id: SlotId, count: u32) {
         self.slots[id.index()].repeat_count = count.max(1);
     }
+
+    /// Set the launch-quantization boundary for a slot.
+    pub fn set_launch_quantum(&mut self, id: SlotId, quantum: LaunchQuantum) {
+        self.slots[id.index()].launch_quantum = quantum;
+    }
+
+    /// Set a slot's transpose, clamped to a +/- two octave range.
+    pub fn set_transpose(&mut self, id: SlotId, semitones: i8) {
+        self.slots[id.index()].transpose = semitones.clamp(-24, 24);
+    }
+
+    /// Set a slot's gain adjustment in dB, clamped to +/- 24dB.
+    pub fn set_gain_db(&mut self, id: SlotId, gain_db: f32) {
+        self.slots[id.index()].gain_db = gain_db.clamp(-24.0, 24.0);
+    }
+
+    /// Set (or clear) the Program Change a slot fires on entry.
+    pub fn set_program_change(&mut self, id: SlotId, program: Option<u8>) {
+        self.slots[id.index()].program_change = program;
+    }
 }
 
 impl Default for SequenceGrid {
@@ -287,6 +725,9 @@ pub struct PlaybackState {
     pub current_iteration: u32,
     /// Total repeat count for current slot
     pub total_iterations: u32,
+    /// Slot that will play next, already rolled from `current_slot`'s
+    /// weighted `next_targets` (see `SequencePlayer::next_slot_id`).
+    pub next_slot: Option<SlotId>,
 }
 
 /// An entry in a sequence: a loop with a repeat count.
@@ -303,6 +744,150 @@ pub struct Sequence {
     pub entries: Vec<SequenceEntry>,
 }
 
+impl Sequence {
+    /// Render this sequence into a flat, time-ordered list of
+    /// `(absolute_clock, message)` pairs, honoring each entry's
+    /// `repeat_count` and wrapping back to the first entry afterward --
+    /// the same order `tick`/`advance_to_next_entry` drive in real time --
+    /// until `total_bars` worth of material has been produced.
+    ///
+    /// This walks the sequence directly rather than driving a
+    /// `SequencePlayer` one clock at a time, so a full performance can be
+    /// `collect()`ed (or fed to `write_smf`) without a manual tick loop.
+    pub fn render(&self, total_bars: u32) -> impl Iterator<Item = (u64, Vec<u8>)> {
+        let total_clocks = total_bars as u64 * BEATS_PER_BAR * CLOCKS_PER_BEAT;
+        let mut events = Vec::new();
+
+        if !self.entries.is_empty() && total_clocks > 0 {
+            let mut clock = 0u64;
+            let mut entry_idx = 0usize;
+            'render: loop {
+                let entry = &self.entries[entry_idx];
+                let length_clocks = entry.loop_data.length_clocks;
+                if length_clocks == 0 {
+                    // Nothing to advance by -- stop rather than spin forever
+                    // on a degenerate zero-length loop.
+                    break;
+                }
+
+                for _ in 0..entry.repeat_count.max(1) {
+                    for event in &entry.loop_data.events {
+                        let absolute = clock + event.clock_position;
+                        if absolute >= total_clocks {
+                            break 'render;
+                        }
+                        events.push((absolute, event.message.clone()));
+                    }
+                    clock += length_clocks;
+                    if clock >= total_clocks {
+                        break 'render;
+                    }
+                }
+
+                entry_idx = (entry_idx + 1) % self.entries.len();
+            }
+        }
+
+        events.sort_by_key(|&(clock, _)| clock);
+        events.into_iter()
+    }
+}
+
+/// Apply a slot's `transpose`/`gain_db` to an outgoing event's message, in
+/// place. Only Note On/Off/Aftertouch carry a note byte to transpose, and
+/// only Note On carries a velocity to scale by gain -- anything else (CC,
+/// pitch bend, Program Change) passes through untouched.
+fn apply_slot_performance(message: &mut [u8], transpose: i8, gain_db: f32) {
+    let Some(&status) = message.first() else {
+        return;
+    };
+    if !matches!(status & 0xF0, 0x80 | 0x90 | 0xA0) {
+        return;
+    }
+    if let Some(note) = message.get_mut(1) {
+        *note = (*note as i16 + transpose as i16).clamp(0, 127) as u8;
+    }
+    if status & 0xF0 == 0x90 {
+        if let Some(velocity) = message.get_mut(2) {
+            let gain = 10f32.powf(gain_db / 20.0);
+            *velocity = (*velocity as f32 * gain).round().clamp(0.0, 127.0) as u8;
+        }
+    }
+}
+
+/// Encode `value` as a MIDI variable-length quantity: 7 bits per byte, with
+/// the high bit set on every byte but the last.
+fn write_vlq(out: &mut Vec<u8>, value: u64) {
+    let mut buffer = value & 0x7F;
+    let mut value = value >> 7;
+    while value > 0 {
+        buffer <<= 8;
+        buffer |= 0x80 | (value & 0x7F);
+        value >>= 7;
+    }
+    loop {
+        out.push((buffer & 0xFF) as u8);
+        if buffer & 0x80 == 0 {
+            break;
+        }
+        buffer >>= 8;
+    }
+}
+
+/// Serialize a rendered performance (see `Sequence::render`) to `writer` as
+/// a format-0 Standard MIDI File: an `MThd` header at `ticks_per_quarter`
+/// resolution, followed by a single `MTrk` chunk of delta-time-encoded
+/// events and a closing end-of-track meta event.
+///
+/// `events` must already be in non-decreasing clock order, as `render`
+/// produces. Clock positions are in the crate's internal 24 ppqn clock
+/// units and are rescaled to `ticks_per_quarter` here.
+pub fn write_smf<W: std::io::Write>(
+    writer: &mut W,
+    events: impl Iterator<Item = (u64, Vec<u8>)>,
+    ticks_per_quarter: u16,
+) -> std::io::Result<()> {
+    writer.write_all(b"MThd")?;
+    writer.write_all(&6u32.to_be_bytes())?;
+    writer.write_all(&0u16.to_be_bytes())?; // format 0
+    writer.write_all(&1u16.to_be_bytes())?; // ntrks
+    writer.write_all(&ticks_per_quarter.to_be_bytes())?;
+
+    let mut track = Vec::new();
+    let mut last_tick = 0u64;
+    for (clock, message) in events {
+        // clock is in 24 ppqn units; rescale to the file's resolution,
+        // rounding to the nearest tick rather than truncating.
+        let tick = (clock * ticks_per_quarter as u64 + CLOCKS_PER_BEAT / 2) / CLOCKS_PER_BEAT;
+        let delta = tick.saturating_sub(last_tick);
+        last_tick = tick;
+        write_vlq(&mut track, delta);
+        track.extend_from_slice(&message);
+    }
+    write_vlq(&mut track, 0);
+    track.extend_from_slice(&[0xFF, 0x2F, 0x00]); // end-of-track meta event
+
+    writer.write_all(b"MTrk")?;
+    writer.write_all(&(track.len() as u32).to_be_bytes())?;
+    writer.write_all(&track)?;
+    Ok(())
+}
+
+/// Boundary at which a sequence queued via `SequencePlayer::queue` swaps in,
+/// instead of cutting over the instant it's queued like `load` does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueQuantum {
+    /// Swap in on the very next tick.
+    Immediate,
+    /// Swap in once the current entry's repeat count is exhausted -- the
+    /// same point the legacy sequence would have advanced to its own next
+    /// entry anyway.
+    NextEntry,
+    /// Swap in on the next downbeat (a multiple of one bar, sized from
+    /// `set_beats_per_bar`).
+    NextBar,
+}
+
 /// Manages playback of a sequence of loops.
 pub struct SequencePlayer {
     // Legacy sequence-based playback
@@ -314,16 +899,66 @@ pub struct SequencePlayer {
     grid: Option<SequenceGrid>,
     /// Current slot being played (for grid mode)
     current_slot: Option<SlotId>,
+    /// Slot queued to launch on its next quantization boundary, Ableton-style
+    /// clip/scene launching (grid mode only).
+    pending_launch: Option<SlotId>,
+    /// Beats per bar, used to size launch-quantum boundaries. Defaults to
+    /// 4/4 until `set_beats_per_bar` is called with the configured meter.
+    beats_per_bar: u64,
+
+    /// Fraction of a swing grid subdivision that every other (the "off")
+    /// subdivision is delayed by. 0.0 disables swing. See `set_swing`.
+    swing_ratio: f64,
+    /// Size, in clock ticks, of the grid swing is applied to (e.g. 6 = a
+    /// sixteenth-note grid at 24 ppqn). 0 disables swing regardless of
+    /// `swing_ratio`.
+    swing_grid_ticks: u64,
 
     // Shared state
     /// Which iteration of the current loop (0-indexed)
     current_iteration: u32,
     /// Index of next event to play in current loop
     next_event_idx: usize,
+    /// Indices at or past `next_event_idx` that were scanned but not yet
+    /// ready to fire (their swung position is still in the future), because
+    /// swing can delay an earlier-stored event past a later-stored one. See
+    /// `swing_position`/`collect_events_at_position`. Rechecked every tick
+    /// before scanning forward, and cleared wherever `next_event_idx` resets
+    /// for a new loop/slot so a stale deferral can't leak across a jump.
+    deferred_event_indices: Vec<usize>,
     /// Clock position when current loop iteration started
     loop_start_clock: u64,
     /// Whether playback is enabled
     pub playing: bool,
+    /// A sequence queued via `queue`, with the boundary it should swap in
+    /// at, so live playback can hand off to a new arrangement without
+    /// glitching or resetting the clock. Resolved every legacy-mode tick.
+    queued_sequence: Option<(Sequence, QueueQuantum)>,
+    /// Real-time input capture for recording into the currently playing
+    /// loop. See `record_event`/`commit_recording`.
+    pub recorder: LoopRecorder,
+    /// Tick-scheduled arpeggiator, always constructed but only fed held
+    /// notes while `arp_armed` -- mirrors `recorder`'s always-present,
+    /// armed-toggle shape. See `arp_note_on`/`arp_note_off`.
+    pub arp: Arpeggiator,
+    /// Whether incoming note messages feed `arp`'s held chord. See
+    /// `set_arp_armed`.
+    arp_armed: bool,
+    /// Notes currently sounding (channel, note), tracked from every 0x90/
+    /// 0x80 message loop/grid playback emits (the arpeggiator's output that
+    /// `tick` appends is not tracked here). Flushed to note-offs (prepended
+    /// to the event batch) on every entry/slot transition, `reset()`,
+    /// `reset_grid()`, and `stop()`, so a note held across one of those
+    /// jumps can't hang past it.
+    active_notes: HashSet<(u8, u8)>,
+    /// xorshift64 PRNG state for weighted NEXT-target rolls. See
+    /// `Arpeggiator`'s identical generator for the random arp pattern.
+    rng_state: u64,
+    /// The target rolled from the current slot's `next_targets` (grid mode
+    /// only), so the table can show a stable "NEXT" highlight for as long
+    /// as this slot plays and `advance_to_next_slot` can reuse the same
+    /// outcome rather than rolling again at the moment of transition.
+    last_rolled_next: Option<SlotId>,
 }
 
 impl SequencePlayer {
@@ -333,11 +968,102 @@ impl SequencePlayer {
             current_entry_idx: 0,
             grid: None,
             current_slot: None,
+            pending_launch: None,
+            beats_per_bar: BEATS_PER_BAR,
+            swing_ratio: 0.0,
+            swing_grid_ticks: 0,
             current_iteration: 0,
             next_event_idx: 0,
+            deferred_event_indices: Vec::new(),
             loop_start_clock: 0,
             playing: false,
+            queued_sequence: None,
+            recorder: LoopRecorder::new(),
+            arp: Arpeggiator::new(ArpPattern::Up, 0, 6, 3),
+            arp_armed: false,
+            active_notes: HashSet::new(),
+            rng_state: 0x2545_F491_4F6C_DD1D,
+            last_rolled_next: None,
+        }
+    }
+
+    /// Advance and return the next xorshift64 PRNG value.
+    fn next_rand(&mut self) -> u64 {
+        let mut x = self.rng_state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.rng_state = x;
+        x
+    }
+
+    /// Weighted-random pick among `targets` (`(slot, weight)` pairs): sums
+    /// the weights, draws a uniform value in `[0, sum)`, and walks the list
+    /// accumulating weight until it passes the draw. Empty list or all-zero
+    /// weights return `None` (stop playback), and a single entry always
+    /// wins regardless of its weight, matching the old deterministic
+    /// `next_slot: Option<SlotId>` behavior.
+    fn choose_weighted_target(&mut self, targets: &[(SlotId, u8)]) -> Option<SlotId> {
+        if let [(slot, _)] = targets {
+            return Some(*slot);
+        }
+        let total: u32 = targets.iter().map(|&(_, weight)| weight as u32).sum();
+        if total == 0 {
+            return None;
+        }
+        let roll = (self.next_rand() % total as u64) as u32;
+        let mut accumulated = 0u32;
+        for &(slot, weight) in targets {
+            accumulated += weight as u32;
+            if roll < accumulated {
+                return Some(slot);
+            }
         }
+        None
+    }
+
+    /// Set the currently-playing grid slot and immediately roll its
+    /// `next_targets`, so `last_rolled_next` reflects this slot's outcome
+    /// for its entire play-through rather than only resolving at the moment
+    /// of transition.
+    fn set_current_slot(&mut self, slot: Option<SlotId>) {
+        self.current_slot = slot;
+        self.last_rolled_next = match (&self.grid, slot) {
+            (Some(grid), Some(id)) => {
+                let targets = grid.get(id).next_targets.clone();
+                self.choose_weighted_target(&targets)
+            }
+            _ => None,
+        };
+    }
+
+    /// Update `active_notes` from a batch of outgoing messages.
+    fn track_notes(&mut self, events: &[Vec<u8>]) {
+        for message in events {
+            let (Some(&status), Some(&note)) = (message.first(), message.get(1)) else {
+                continue;
+            };
+            match status & 0xF0 {
+                0x90 if message.get(2).copied().unwrap_or(0) > 0 => {
+                    self.active_notes.insert((status & 0x0F, note));
+                }
+                0x90 | 0x80 => {
+                    self.active_notes.remove(&(status & 0x0F, note));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Synthesize a note-off for every note currently tracked as sounding,
+    /// then clear the active set. Called at every point playback can jump
+    /// out from under a held note: entry/slot transitions, `reset()`,
+    /// `reset_grid()`, and `stop()`.
+    fn flush_active_notes(&mut self) -> Vec<Vec<u8>> {
+        self.active_notes
+            .drain()
+            .map(|(channel, note)| vec![0x80 | channel, note, 0])
+            .collect()
     }
 
     /// Load a sequence for playback.
@@ -346,6 +1072,7 @@ impl SequencePlayer {
         self.current_entry_idx = 0;
         self.current_iteration = 0;
         self.next_event_idx = 0;
+        self.deferred_event_indices.clear();
         self.loop_start_clock = 0;
     }
 
@@ -354,21 +1081,63 @@ impl SequencePlayer {
         self.current_entry_idx = 0;
         self.current_iteration = 0;
         self.next_event_idx = 0;
+        self.deferred_event_indices.clear();
         self.loop_start_clock = 0;
         self.playing = true;
     }
 
-    /// Stop playback.
-    pub fn stop(&mut self) {
+    /// Stop playback. Returns note-offs for anything still sounding so the
+    /// caller can send them out before the transport actually stops.
+    pub fn stop(&mut self) -> Vec<Vec<u8>> {
         self.playing = false;
+        self.flush_active_notes()
     }
 
-    /// Reset to sequence start (called when transport restarts).
-    pub fn reset(&mut self) {
+    /// Reset to sequence start (called when transport restarts). Returns
+    /// note-offs for anything still sounding, same as `stop()`.
+    pub fn reset(&mut self) -> Vec<Vec<u8>> {
         self.current_entry_idx = 0;
         self.current_iteration = 0;
         self.next_event_idx = 0;
+        self.deferred_event_indices.clear();
         self.loop_start_clock = 0;
+        // The record head is just `loop_start_clock` seen from the
+        // recorder's side, so resetting it here keeps recorded material
+        // phase-locked to the loop the same way playback is. Anything
+        // captured but not yet committed belonged to the old phase, so it
+        // doesn't carry over.
+        self.recorder.captured.clear();
+        self.flush_active_notes()
+    }
+
+    /// Queue `sequence` to replace the one currently playing at `quantum`'s
+    /// boundary, rather than cutting over immediately like `load` does.
+    /// Resolved every legacy-mode tick; until the boundary is crossed,
+    /// `current_loop_name`/`current_state` keep reflecting the outgoing
+    /// sequence and `queued_sequence_pending` reports the swap is waiting.
+    pub fn queue(&mut self, sequence: Sequence, quantum: QueueQuantum) {
+        self.queued_sequence = Some((sequence, quantum));
+    }
+
+    /// Whether a sequence is queued to swap in (for UI display).
+    pub fn queued_sequence_pending(&self) -> bool {
+        self.queued_sequence.is_some()
+    }
+
+    /// Swap in a queued sequence (see `queue`), resetting playback to its
+    /// first entry phase-locked at `clock_count`. Doesn't flush notes
+    /// itself -- callers already do that via `flush_active_notes` at every
+    /// point a swap can happen, same as an entry/slot transition.
+    fn apply_queued_sequence(&mut self, clock_count: u64) {
+        if let Some((sequence, _)) = self.queued_sequence.take() {
+            self.sequence = Some(sequence);
+        }
+        self.current_entry_idx = 0;
+        self.current_iteration = 0;
+        self.next_event_idx = 0;
+        self.deferred_event_indices.clear();
+        self.loop_start_clock = clock_count;
+        self.recorder.captured.clear();
     }
 
     /// Get the name of the currently playing loop.
@@ -389,10 +1158,12 @@ impl SequencePlayer {
 
     /// Load a grid for playback (replaces legacy sequence).
     pub fn load_grid(&mut self, grid: SequenceGrid) {
-        self.grid = Some(grid.clone());
-        self.current_slot = Some(grid.start_slot);
+        let start_slot = grid.start_slot;
+        self.grid = Some(grid);
+        self.set_current_slot(Some(start_slot));
         self.current_iteration = 0;
         self.next_event_idx = 0;
+        self.deferred_event_indices.clear();
         self.loop_start_clock = 0;
         // Clear legacy sequence
         self.sequence = None;
@@ -410,19 +1181,23 @@ impl SequencePlayer {
                 .is_none()
             {
                 // Current slot no longer valid, reset to start
-                self.reset_grid();
+                let _ = self.reset_grid();
             }
         }
     }
 
-    /// Reset grid playback to start slot.
-    pub fn reset_grid(&mut self) {
-        if let Some(ref grid) = self.grid {
-            self.current_slot = Some(grid.start_slot);
+    /// Reset grid playback to start slot. Returns note-offs for anything
+    /// still sounding, same as `reset()`.
+    pub fn reset_grid(&mut self) -> Vec<Vec<u8>> {
+        if let Some(start_slot) = self.grid.as_ref().map(|g| g.start_slot) {
+            self.set_current_slot(Some(start_slot));
         }
         self.current_iteration = 0;
         self.next_event_idx = 0;
+        self.deferred_event_indices.clear();
         self.loop_start_clock = 0;
+        self.recorder.captured.clear();
+        self.flush_active_notes()
     }
 
     /// Get playback state for UI display (grid mode).
@@ -435,14 +1210,16 @@ impl SequencePlayer {
             current_slot: slot_id,
             current_iteration: self.current_iteration + 1,
             total_iterations: slot.repeat_count,
+            next_slot: self.last_rolled_next,
         })
     }
 
-    /// Get the next slot that will play (for UI highlighting).
+    /// Get the next slot that will play (for UI highlighting): the target
+    /// already rolled from the current slot's `next_targets` (see
+    /// `set_current_slot`), so a weighted branch's outcome is known and
+    /// stable for the whole time the current slot plays.
     pub fn next_slot_id(&self) -> Option<SlotId> {
-        let grid = self.grid.as_ref()?;
-        let current = self.current_slot?;
-        grid.get(current).next_slot
+        self.last_rolled_next
     }
 
     /// Get current slot ID (for UI).
@@ -455,8 +1232,144 @@ impl SequencePlayer {
         self.grid.is_some()
     }
 
-    /// Called on each clock tick. Returns events that should be sent now.
+    /// Set the beats-per-bar used to size launch-quantum boundaries (see
+    /// `request_slot`). Defaults to 4/4 if never called.
+    pub fn set_beats_per_bar(&mut self, beats_per_bar: u64) {
+        self.beats_per_bar = beats_per_bar.max(1);
+    }
+
+    /// Configure swing: every other `grid_ticks` subdivision is delayed by
+    /// `ratio` of the subdivision's length before its events fire (e.g.
+    /// `grid_ticks = 6` swings sixteenth notes at 24 ppqn). A per-sequence
+    /// setting, applied inside `tick`/`tick_grid` so both playback and any
+    /// scheduled events groove consistently. `grid_ticks = 0` disables
+    /// swing regardless of `ratio`.
+    pub fn set_swing(&mut self, ratio: f64, grid_ticks: u64) {
+        self.swing_ratio = ratio.clamp(0.0, 1.0);
+        self.swing_grid_ticks = grid_ticks;
+    }
+
+    /// Delay `clock_position` if it falls on an "off" swing subdivision.
+    /// No-op (returns `clock_position` unchanged) when swing is disabled.
+    fn swing_position(&self, clock_position: u64) -> u64 {
+        if self.swing_grid_ticks == 0 || self.swing_ratio == 0.0 {
+            return clock_position;
+        }
+        let step = clock_position / self.swing_grid_ticks;
+        if step % 2 == 1 {
+            let delay = (self.swing_ratio * self.swing_grid_ticks as f64).round() as u64;
+            clock_position + delay
+        } else {
+            clock_position
+        }
+    }
+
+    /// Queue a slot to become active on its next launch-quantum boundary
+    /// (Ableton-style clip launching) rather than switching instantly.
+    /// Checked and resolved every clock tick by `tick_grid`.
+    pub fn request_slot(&mut self, slot_id: SlotId) {
+        if self.grid.is_some() {
+            self.pending_launch = Some(slot_id);
+        }
+    }
+
+    /// Slot currently queued to launch, if any (for UI highlighting).
+    pub fn pending_launch(&self) -> Option<SlotId> {
+        self.pending_launch
+    }
+
+    /// Position within whatever loop is currently playing (grid slot or
+    /// legacy sequence entry), using the same elapsed/length math as
+    /// `tick`/`tick_grid`. `None` if nothing is playing or has no loop
+    /// loaded yet, i.e. there's no phase to lock a recording to.
+    fn current_loop_position(&self, clock_count: u64) -> Option<u64> {
+        let length_clocks = if let Some(grid) = &self.grid {
+            grid.get(self.current_slot?).loop_data.as_ref()?.length_clocks
+        } else {
+            self.sequence.as_ref()?.entries.get(self.current_entry_idx)?.loop_data.length_clocks
+        };
+
+        if length_clocks == 0 {
+            return None;
+        }
+        let elapsed = clock_count.saturating_sub(self.loop_start_clock);
+        Some(elapsed % length_clocks)
+    }
+
+    pub fn is_arp_armed(&self) -> bool {
+        self.arp_armed
+    }
+
+    pub fn set_arp_armed(&mut self, armed: bool) {
+        self.arp_armed = armed;
+    }
+
+    /// Feed an incoming Note-On into the held chord `arp` arpeggiates, if
+    /// armed. No-op otherwise, so held-note tracking doesn't accumulate
+    /// while the feature is off.
+    pub fn arp_note_on(&mut self, note: u8, clock_count: u64) {
+        if self.arp_armed {
+            self.arp.note_on(note, clock_count);
+        }
+    }
+
+    /// Feed an incoming Note-Off (or Note-On with velocity 0) into the held
+    /// chord `arp` arpeggiates. See `arp_note_on`.
+    pub fn arp_note_off(&mut self, note: u8, clock_count: u64) {
+        if self.arp_armed {
+            self.arp.note_off(note, clock_count);
+        }
+    }
+
+    /// Capture one incoming MIDI event for recording, phase-locked to the
+    /// loop currently playing. No-op if the recorder isn't armed, or if
+    /// nothing is playing yet to lock the recording's phase to.
+    pub fn record_event(&mut self, channel: u8, message: Vec<u8>, clock_count: u64) {
+        if !self.recorder.is_armed() {
+            return;
+        }
+        if let Some(position) = self.current_loop_position(clock_count) {
+            self.recorder.capture(channel, message, position);
+        }
+    }
+
+    /// Commit everything captured so far into the loop currently playing
+    /// (grid slot or legacy sequence entry), replacing or overdubbing it
+    /// per the recorder's mode. Does nothing if nothing is playing or the
+    /// current slot/entry has no loop loaded to record into.
+    ///
+    /// `tick`/`tick_grid` call this automatically at every loop-iteration
+    /// boundary while armed, so whatever was captured during one pass
+    /// becomes part of the event list -- and gets played back -- starting
+    /// on the next one.
+    pub fn commit_recording(&mut self) {
+        if let Some(grid) = &mut self.grid {
+            if let Some(slot_id) = self.current_slot {
+                if let Some(loop_data) = grid.get_mut(slot_id).loop_data.as_mut() {
+                    self.recorder.commit(loop_data);
+                }
+            }
+            return;
+        }
+        if let Some(sequence) = &mut self.sequence {
+            if let Some(entry) = sequence.entries.get_mut(self.current_entry_idx) {
+                self.recorder.commit(&mut entry.loop_data);
+            }
+        }
+    }
+
+    /// Called on each clock tick. Returns events that should be sent now:
+    /// whatever the loop/grid playback produces, plus any notes `arp` fires
+    /// this tick (independent of `arp_armed` -- once a chord is held,
+    /// releasing it naturally drains whatever's left scheduled; arming
+    /// only gates whether new notes get fed in, not whether `arp` ticks).
     pub fn tick(&mut self, clock_count: u64) -> Vec<Vec<u8>> {
+        let mut events = self.tick_loop(clock_count);
+        events.append(&mut self.arp.tick(clock_count));
+        events
+    }
+
+    fn tick_loop(&mut self, clock_count: u64) -> Vec<Vec<u8>> {
         if !self.playing {
             return Vec::new();
         }
@@ -466,6 +1379,23 @@ impl SequencePlayer {
             return self.tick_grid(clock_count);
         }
 
+        // An Immediate or next-bar queued swap doesn't wait for the current
+        // entry to naturally finish, so it's resolved before anything else.
+        if let Some((_, quantum)) = &self.queued_sequence {
+            let due = match quantum {
+                QueueQuantum::Immediate => true,
+                QueueQuantum::NextBar => clock_count % (self.beats_per_bar * CLOCKS_PER_BEAT) == 0,
+                QueueQuantum::NextEntry => false,
+            };
+            if due {
+                let mut events = self.flush_active_notes();
+                self.apply_queued_sequence(clock_count);
+                events.append(&mut self.collect_events_at_position(0));
+                self.track_notes(&events);
+                return events;
+            }
+        }
+
         // Legacy sequence mode
         let sequence = match &self.sequence {
             Some(s) => s,
@@ -491,44 +1421,98 @@ impl SequencePlayer {
 
         // Check if we need to advance to next entry
         if iteration >= repeat_count as u64 {
-            self.advance_to_next_entry(clock_count);
+            if self.recorder.is_armed() {
+                self.commit_recording();
+            }
+            // Flush notes still sounding before jumping away from this
+            // entry, so nothing held across the boundary hangs.
+            let mut events = self.flush_active_notes();
+            if matches!(
+                self.queued_sequence.as_ref().map(|(_, q)| *q),
+                Some(QueueQuantum::NextEntry)
+            ) {
+                self.apply_queued_sequence(clock_count);
+            } else {
+                self.advance_to_next_entry(clock_count);
+            }
             // Return events at position 0 of the new entry
-            return self.collect_events_at_position(0);
+            events.append(&mut self.collect_events_at_position(0));
+            self.track_notes(&events);
+            return events;
         }
 
-        // Check if we've wrapped to a new iteration within current loop
+        // Check if we've wrapped to a new iteration within current loop:
+        // commit anything recorded during the pass that just finished so
+        // it's part of the event list (and sent out) on this new one.
         if iteration as u32 > self.current_iteration {
+            if self.recorder.is_armed() {
+                self.commit_recording();
+            }
             self.current_iteration = iteration as u32;
             self.next_event_idx = 0;
+            self.deferred_event_indices.clear();
         }
 
         // Collect events at current position
-        self.collect_events_at_position(position_in_loop)
+        let events = self.collect_events_at_position(position_in_loop);
+        self.track_notes(&events);
+        events
     }
 
-    /// Tick for grid-based playback.
+    /// Tick for grid-based playback. Before the normal per-slot advance
+    /// logic, checks whether a queued launch has crossed its quantization
+    /// boundary and, if so, swaps in the new slot with its loop phase reset
+    /// so the clip starts cleanly on the downbeat.
     fn tick_grid(&mut self, clock_count: u64) -> Vec<Vec<u8>> {
+        // Notes flushed by a launch/slot swap below get returned alongside
+        // whatever the rest of this tick produces, rather than discarded by
+        // an early return if the new slot turns out empty.
+        let mut prefix = Vec::new();
+
+        if let Some(slot_id) = self.pending_launch {
+            let quantum = self
+                .grid
+                .as_ref()
+                .map(|g| g.get(slot_id).launch_quantum)
+                .unwrap_or_default();
+            let quantum_clocks = quantum.clocks(self.beats_per_bar);
+            if clock_count % quantum_clocks == 0 {
+                // A new clip is about to start: flush whatever the old one
+                // left sounding so it can't hang past the swap.
+                prefix.append(&mut self.flush_active_notes());
+                self.set_current_slot(Some(slot_id));
+                if let Some(program_change) = self.program_change_event(slot_id) {
+                    prefix.push(program_change);
+                }
+                self.pending_launch = None;
+                self.current_iteration = 0;
+                self.next_event_idx = 0;
+                self.deferred_event_indices.clear();
+                self.loop_start_clock = clock_count;
+            }
+        }
+
         let grid = match &self.grid {
             Some(g) => g,
-            None => return Vec::new(),
+            None => return prefix,
         };
 
         let slot_id = match self.current_slot {
             Some(id) => id,
-            None => return Vec::new(),
+            None => return prefix,
         };
 
         let slot = grid.get(slot_id);
         let loop_data = match &slot.loop_data {
             Some(l) => l,
-            None => return Vec::new(), // Empty slot, no events
+            None => return prefix, // Empty slot, no events
         };
 
         let repeat_count = slot.repeat_count;
         let length_clocks = loop_data.length_clocks;
 
         if loop_data.events.is_empty() || length_clocks == 0 {
-            return Vec::new();
+            return prefix;
         }
 
         // Calculate position within current loop
@@ -538,31 +1522,49 @@ impl SequencePlayer {
 
         // Check if we need to advance to next slot
         if iteration >= repeat_count as u64 {
+            if self.recorder.is_armed() {
+                self.commit_recording();
+            }
+            prefix.append(&mut self.flush_active_notes());
             self.advance_to_next_slot(clock_count);
+            if let Some(slot_id) = self.current_slot {
+                if let Some(program_change) = self.program_change_event(slot_id) {
+                    prefix.push(program_change);
+                }
+            }
             // Return events at position 0 of the new slot
-            return self.collect_grid_events_at_position(0);
+            prefix.append(&mut self.collect_grid_events_at_position(0));
+            self.track_notes(&prefix);
+            return prefix;
         }
 
-        // Check if we've wrapped to a new iteration within current loop
+        // Check if we've wrapped to a new iteration within current loop:
+        // commit anything recorded during the pass that just finished so
+        // it's part of the event list (and sent out) on this new one.
         if iteration as u32 > self.current_iteration {
+            if self.recorder.is_armed() {
+                self.commit_recording();
+            }
             self.current_iteration = iteration as u32;
             self.next_event_idx = 0;
+            self.deferred_event_indices.clear();
         }
 
         // Collect events at current position
-        self.collect_grid_events_at_position(position_in_loop)
+        prefix.append(&mut self.collect_grid_events_at_position(position_in_loop));
+        self.track_notes(&prefix);
+        prefix
     }
 
     fn advance_to_next_slot(&mut self, clock_count: u64) {
-        if let Some(grid) = &self.grid {
-            if let Some(current) = self.current_slot {
-                let next = grid.get(current).next_slot;
-                self.current_slot = next;
-                // If next is None, playback stops (slot will be None)
-            }
-        }
+        // The outgoing slot's next_targets were already rolled when it
+        // became current (see `set_current_slot`), so the "NEXT" row the
+        // table highlighted is exactly what plays now -- no second roll.
+        let next = self.last_rolled_next;
+        self.set_current_slot(next);
         self.current_iteration = 0;
         self.next_event_idx = 0;
+        self.deferred_event_indices.clear();
         self.loop_start_clock = clock_count;
     }
 
@@ -577,30 +1579,66 @@ impl SequencePlayer {
             None => return Vec::new(),
         };
 
-        let events_ref = match &grid.get(slot_id).loop_data {
+        let slot = grid.get(slot_id);
+        let events_ref = match &slot.loop_data {
             Some(l) => &l.events,
             None => return Vec::new(),
         };
+        let transpose = slot.transpose;
+        let gain_db = slot.gain_db;
 
         let mut events = Vec::new();
-        while self.next_event_idx < events_ref.len() {
-            let event = &events_ref[self.next_event_idx];
-            if event.clock_position <= position {
-                events.push(event.message.clone());
-                self.next_event_idx += 1;
+        let mut still_deferred = Vec::new();
+        for idx in std::mem::take(&mut self.deferred_event_indices) {
+            let event = &events_ref[idx];
+            if self.swing_position(event.clock_position) <= position {
+                let mut message = event.message.clone();
+                apply_slot_performance(&mut message, transpose, gain_db);
+                events.push(message);
             } else {
-                break;
+                still_deferred.push(idx);
             }
         }
+        while self.next_event_idx < events_ref.len() {
+            let idx = self.next_event_idx;
+            let event = &events_ref[idx];
+            if self.swing_position(event.clock_position) <= position {
+                let mut message = event.message.clone();
+                apply_slot_performance(&mut message, transpose, gain_db);
+                events.push(message);
+            } else {
+                still_deferred.push(idx);
+            }
+            self.next_event_idx += 1;
+        }
+        self.deferred_event_indices = still_deferred;
         events
     }
 
+    /// Build the Program Change message (if any) `slot_id` should fire on
+    /// entry (see `SequenceSlot::program_change`), on the channel its loop's
+    /// own events already use (falling back to channel 0 for an empty or
+    /// not-yet-rendered slot).
+    fn program_change_event(&self, slot_id: SlotId) -> Option<Vec<u8>> {
+        let grid = self.grid.as_ref()?;
+        let slot = grid.get(slot_id);
+        let program = slot.program_change?;
+        let channel = slot
+            .loop_data
+            .as_ref()
+            .and_then(|l| l.events.first())
+            .map(|e| e.channel)
+            .unwrap_or(0);
+        Some(vec![0xC0 | (channel & 0x0F), program])
+    }
+
     // Legacy sequence helpers
     fn advance_to_next_entry(&mut self, clock_count: u64) {
         let num_entries = self.sequence.as_ref().unwrap().entries.len();
         self.current_entry_idx = (self.current_entry_idx + 1) % num_entries;
         self.current_iteration = 0;
         self.next_event_idx = 0;
+        self.deferred_event_indices.clear();
         self.loop_start_clock = clock_count;
     }
 
@@ -610,15 +1648,26 @@ impl SequencePlayer {
             .events;
 
         let mut events = Vec::new();
+        let mut still_deferred = Vec::new();
+        for idx in std::mem::take(&mut self.deferred_event_indices) {
+            let event = &events_ref[idx];
+            if self.swing_position(event.clock_position) <= position {
+                events.push(event.message.clone());
+            } else {
+                still_deferred.push(idx);
+            }
+        }
         while self.next_event_idx < events_ref.len() {
-            let event = &events_ref[self.next_event_idx];
-            if event.clock_position <= position {
+            let idx = self.next_event_idx;
+            let event = &events_ref[idx];
+            if self.swing_position(event.clock_position) <= position {
                 events.push(event.message.clone());
-                self.next_event_idx += 1;
             } else {
-                break;
+                still_deferred.push(idx);
             }
+            self.next_event_idx += 1;
         }
+        self.deferred_event_indices = still_deferred;
         events
     }
 }
@@ -680,6 +1729,321 @@ mod tests {
         }
     }
 
+    // ============ Track / Step Tests ============
+
+    #[test]
+    fn test_time_division_clocks_per_step() {
+        assert_eq!(TimeDivision::ThirtySecond.clocks_per_step(), 3);
+        assert_eq!(TimeDivision::Sixteenth.clocks_per_step(), 6);
+        assert_eq!(TimeDivision::Eighth.clocks_per_step(), 12);
+        assert_eq!(TimeDivision::Quarter.clocks_per_step(), 24);
+        assert_eq!(TimeDivision::Whole.clocks_per_step(), 96);
+    }
+
+    #[test]
+    fn test_track_to_loop_emits_note_on_and_gated_off() {
+        let mut track = Track::new(TimeDivision::Sixteenth, 4, 0);
+        track.steps[0] = Some(Step {
+            note: 60,
+            velocity: 100,
+            pitch_bend: 0x2000,
+            length_step_cents: 50,
+        });
+
+        let loop_data = track.to_loop("test-track");
+        assert_eq!(loop_data.length_clocks, 4 * 6);
+        assert_eq!(loop_data.events.len(), 2);
+        assert_eq!(loop_data.events[0].clock_position, 0);
+        assert_eq!(loop_data.events[0].message, vec![0x90, 60, 100]);
+        // 50% gate of a 6-clock step = 3 clocks
+        assert_eq!(loop_data.events[1].clock_position, 3);
+        assert_eq!(loop_data.events[1].message, vec![0x80, 60, 0]);
+    }
+
+    #[test]
+    fn test_track_empty_steps_produce_no_events() {
+        let track = Track::new(TimeDivision::Quarter, 8, 0);
+        let loop_data = track.to_loop("empty");
+        assert!(loop_data.events.is_empty());
+        assert_eq!(loop_data.length_clocks, 8 * 24);
+    }
+
+    #[test]
+    fn test_track_tied_note_cancels_stale_note_off() {
+        // Full-length gate on consecutive steps with the same note should
+        // retrigger cleanly rather than emitting two overlapping note-offs.
+        let mut track = Track::new(TimeDivision::Sixteenth, 2, 0);
+        let step = Step {
+            note: 60,
+            velocity: 100,
+            pitch_bend: 0x2000,
+            length_step_cents: 100,
+        };
+        track.steps[0] = Some(step);
+        track.steps[1] = Some(step);
+
+        let loop_data = track.to_loop("tied");
+        // Only one note-off should remain: the second step's, at the end of
+        // the track. The first step's note-off (which would have landed
+        // exactly when the second note-on fires) is canceled.
+        let note_offs: Vec<_> = loop_data
+            .events
+            .iter()
+            .filter(|e| e.message[0] == 0x80)
+            .collect();
+        assert_eq!(note_offs.len(), 1);
+        assert_eq!(note_offs[0].clock_position, 11); // clamped to length-1
+    }
+
+    #[test]
+    fn test_track_pitch_bend_emitted_when_not_centered() {
+        let mut track = Track::new(TimeDivision::Quarter, 1, 0);
+        track.steps[0] = Some(Step {
+            note: 60,
+            velocity: 100,
+            pitch_bend: 0x3000,
+            length_step_cents: 85,
+        });
+
+        let loop_data = track.to_loop("bend");
+        assert!(loop_data
+            .events
+            .iter()
+            .any(|e| e.message[0] == 0xE0));
+    }
+
+    #[test]
+    fn test_load_track_renders_loop_data_and_marks_slot() {
+        let mut grid = SequenceGrid::new();
+        let mut track = Track::new(TimeDivision::Quarter, 2, 0);
+        track.steps[0] = Some(Step {
+            note: 60,
+            velocity: 100,
+            pitch_bend: 0x2000,
+            length_step_cents: 85,
+        });
+
+        grid.load_track(SlotId('A'), track);
+
+        let slot = grid.get(SlotId('A'));
+        assert!(slot.has_track());
+        assert!(slot.has_loop());
+        assert_eq!(slot.loop_data.as_ref().unwrap().events[0].message, vec![0x90, 60, 100]);
+    }
+
+    #[test]
+    fn test_set_track_step_re_renders_loop_data() {
+        let mut grid = SequenceGrid::new();
+        grid.load_track(SlotId('A'), Track::new(TimeDivision::Quarter, 2, 0));
+        assert!(grid.get(SlotId('A')).loop_data.as_ref().unwrap().events.is_empty());
+
+        grid.set_track_step(
+            SlotId('A'),
+            1,
+            Some(Step {
+                note: 67,
+                velocity: 90,
+                pitch_bend: 0x2000,
+                length_step_cents: 50,
+            }),
+        );
+
+        let slot = grid.get(SlotId('A'));
+        assert_eq!(slot.track_data.as_ref().unwrap().steps[1].unwrap().note, 67);
+        assert!(slot
+            .loop_data
+            .as_ref()
+            .unwrap()
+            .events
+            .iter()
+            .any(|e| e.message == vec![0x90, 67, 90]));
+    }
+
+    #[test]
+    fn test_set_track_step_no_op_without_track_loaded() {
+        let mut grid = SequenceGrid::new();
+        grid.load_loop(SlotId('A'), make_test_loop_named("loop-a", 60));
+
+        grid.set_track_step(SlotId('A'), 0, None);
+
+        assert!(!grid.get(SlotId('A')).has_track());
+        assert!(grid.get(SlotId('A')).has_loop());
+    }
+
+    // ============ Launch Quantization Tests ============
+
+    fn make_grid_with_two_slots() -> SequenceGrid {
+        let mut grid = SequenceGrid::new();
+        grid.load_loop(SlotId('A'), make_test_loop_named("loop-a", 60));
+        grid.load_loop(SlotId('B'), make_test_loop_named("loop-b", 64));
+        grid
+    }
+
+    #[test]
+    fn test_launch_quantum_clocks() {
+        assert_eq!(LaunchQuantum::OneBeat.clocks(4), 24);
+        assert_eq!(LaunchQuantum::OneBar.clocks(4), 96);
+        assert_eq!(LaunchQuantum::TwoBars.clocks(4), 192);
+        assert_eq!(LaunchQuantum::FourBars.clocks(4), 384);
+    }
+
+    #[test]
+    fn test_request_slot_waits_for_quantum_boundary() {
+        let mut player = SequencePlayer::new();
+        player.load_grid(make_grid_with_two_slots());
+        player.playing = true;
+
+        player.request_slot(SlotId('B'));
+        // Default quantum is 1 bar (96 clocks); not yet at a boundary.
+        player.tick(10);
+        assert_eq!(player.current_slot_id(), Some(SlotId('A')));
+        assert_eq!(player.pending_launch(), Some(SlotId('B')));
+
+        // Crossing the boundary flushes A's still-held note (its note-off at
+        // clock 48 was never ticked), swaps the slot and resets its phase.
+        let events = player.tick(96);
+        assert_eq!(player.current_slot_id(), Some(SlotId('B')));
+        assert_eq!(player.pending_launch(), None);
+        assert_eq!(events, vec![vec![0x80, 60, 0], vec![0x90, 64, 100]]);
+    }
+
+    #[test]
+    fn test_request_slot_honors_per_slot_quantum() {
+        let mut player = SequencePlayer::new();
+        let mut grid = make_grid_with_two_slots();
+        grid.set_launch_quantum(SlotId('B'), LaunchQuantum::OneBeat);
+        player.load_grid(grid);
+        player.playing = true;
+
+        player.request_slot(SlotId('B'));
+        // 1-beat quantum = 24 clocks, so this boundary is much sooner.
+        player.tick(1);
+        assert_eq!(player.current_slot_id(), Some(SlotId('A')));
+
+        player.tick(24);
+        assert_eq!(player.current_slot_id(), Some(SlotId('B')));
+    }
+
+    // ============ Per-slot Performance Parameter Tests ============
+
+    #[test]
+    fn test_apply_slot_performance_transposes_note_messages() {
+        let mut message = vec![0x90, 60, 100];
+        apply_slot_performance(&mut message, 12, 0.0);
+        assert_eq!(message, vec![0x90, 72, 100]);
+    }
+
+    #[test]
+    fn test_apply_slot_performance_clamps_transpose_to_valid_range() {
+        let mut message = vec![0x80, 120, 0];
+        apply_slot_performance(&mut message, 24, 0.0);
+        assert_eq!(message[1], 127);
+    }
+
+    #[test]
+    fn test_apply_slot_performance_scales_note_on_velocity_by_gain() {
+        let mut message = vec![0x90, 60, 100];
+        apply_slot_performance(&mut message, 0, -6.0);
+        // -6dB is roughly half amplitude.
+        assert!((49..=51).contains(&message[2]));
+    }
+
+    #[test]
+    fn test_apply_slot_performance_leaves_non_note_messages_untouched() {
+        let mut cc = vec![0xB0, 7, 100];
+        apply_slot_performance(&mut cc, 12, -6.0);
+        assert_eq!(cc, vec![0xB0, 7, 100]);
+    }
+
+    #[test]
+    fn test_grid_playback_applies_slot_transpose_and_gain() {
+        let mut grid = make_grid_with_two_slots();
+        grid.set_transpose(SlotId('A'), 12);
+        grid.set_gain_db(SlotId('A'), -6.0);
+        let mut player = SequencePlayer::new();
+        player.load_grid(grid);
+        player.playing = true;
+
+        let events = player.tick(0);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0][0], 0x90);
+        assert_eq!(events[0][1], 72); // transposed up an octave
+        assert!((49..=51).contains(&events[0][2])); // ~-6dB velocity
+    }
+
+    #[test]
+    fn test_slot_entry_fires_program_change() {
+        let mut grid = make_grid_with_two_slots();
+        grid.set_program_change(SlotId('B'), Some(42));
+        let mut player = SequencePlayer::new();
+        player.load_grid(grid);
+        player.playing = true;
+
+        player.request_slot(SlotId('B'));
+        let events = player.tick(96); // default 1-bar quantum boundary
+        assert!(events.iter().any(|e| e == &vec![0xC0, 42]));
+    }
+
+    #[test]
+    fn test_no_program_change_event_when_unset() {
+        let mut player = SequencePlayer::new();
+        player.load_grid(make_grid_with_two_slots());
+        assert!(player.program_change_event(SlotId('A')).is_none());
+    }
+
+    // ============ Swing Tests ============
+
+    #[test]
+    fn test_swing_position_delays_only_off_subdivisions() {
+        let mut player = SequencePlayer::new();
+        player.set_swing(0.5, 6);
+        assert_eq!(player.swing_position(0), 0); // step 0 (even): untouched
+        assert_eq!(player.swing_position(6), 9); // step 1 (odd): delayed by 3
+        assert_eq!(player.swing_position(12), 12); // step 2 (even): untouched
+    }
+
+    #[test]
+    fn test_swing_preserves_ordering_across_a_delayed_event() {
+        // Reproduces the gated-note-off-near-a-step-boundary shape
+        // `Track::to_loop` produces: an odd-step event (here clock 11) swings
+        // out to 14, while the very next stored event (clock 12, even step)
+        // stays put -- so it becomes *ready* before the earlier-stored event
+        // does. The collector must not block on storage order and hold
+        // event2 back until event1 catches up.
+        let mut player = SequencePlayer::new();
+        player.set_swing(0.5, 6); // delay = round(0.5 * 6) = 3
+        player.load(Sequence {
+            entries: vec![SequenceEntry {
+                loop_data: Loop {
+                    name: "swing-test".to_string(),
+                    length_clocks: 96,
+                    events: vec![
+                        LoopEvent {
+                            clock_position: 11, // odd step -> swings to 14
+                            channel: 0,
+                            message: vec![0x90, 60, 100],
+                        },
+                        LoopEvent {
+                            clock_position: 12, // even step -> stays at 12
+                            channel: 0,
+                            message: vec![0x90, 61, 100],
+                        },
+                    ],
+                },
+                repeat_count: 1,
+            }],
+        });
+        player.start();
+
+        // At tick 12, only the even-step event (not delayed) is ready.
+        let events = player.tick(12);
+        assert_eq!(events, vec![vec![0x90, 61, 100]]);
+
+        // At tick 14, the delayed odd-step event finally catches up.
+        let events = player.tick(14);
+        assert_eq!(events, vec![vec![0x90, 60, 100]]);
+    }
+
     // ============ Sequence Player Tests ============
 
     fn make_test_loop_named(name: &str, note: u8) -> Loop {
@@ -1003,4 +2367,418 @@ mod tests {
         assert!(player.tick(0).is_empty());
         assert!(player.tick(96).is_empty());
     }
+
+    // ============ Hanging-note Tests ============
+
+    /// A loop whose note-on is never followed by a note-off of its own, so
+    /// it's still "sounding" by the time the loop boundary is crossed.
+    fn make_held_note_loop(name: &str, note: u8) -> Loop {
+        Loop {
+            name: name.to_string(),
+            length_clocks: 96,
+            events: vec![LoopEvent {
+                clock_position: 0,
+                channel: 0,
+                message: vec![0x90, note, 100],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_entry_transition_flushes_held_note() {
+        let mut player = SequencePlayer::new();
+        player.load(Sequence {
+            entries: vec![
+                SequenceEntry {
+                    loop_data: make_held_note_loop("loop1", 60),
+                    repeat_count: 1,
+                },
+                SequenceEntry {
+                    loop_data: make_test_loop_named("loop2", 64),
+                    repeat_count: 1,
+                },
+            ],
+        });
+        player.start();
+
+        player.tick(0); // Note 60 on, never turned off by loop1 itself
+
+        // Crossing into loop2 should flush the still-sounding note before
+        // loop2's own note-on.
+        let events = player.tick(96);
+        assert_eq!(events[0], vec![0x80, 60, 0]);
+        assert_eq!(events[1], vec![0x90, 64, 100]);
+    }
+
+    #[test]
+    fn test_slot_transition_flushes_held_note() {
+        let mut player = SequencePlayer::new();
+        let mut grid = SequenceGrid::new();
+        grid.load_loop(SlotId('A'), make_held_note_loop("loop-a", 60));
+        grid.load_loop(SlotId('B'), make_test_loop_named("loop-b", 64));
+        grid.set_next(SlotId('A'), Some(SlotId('B')));
+        player.load_grid(grid);
+        player.playing = true;
+
+        player.tick(0); // Note 60 on, never turned off by slot A itself
+
+        let events = player.tick(96);
+        assert_eq!(events[0], vec![0x80, 60, 0]);
+        assert_eq!(events[1], vec![0x90, 64, 100]);
+    }
+
+    #[test]
+    fn test_reset_flushes_held_note() {
+        let mut player = SequencePlayer::new();
+        player.load(Sequence {
+            entries: vec![SequenceEntry {
+                loop_data: make_held_note_loop("held", 60),
+                repeat_count: 10,
+            }],
+        });
+        player.start();
+
+        player.tick(0); // Note 60 on, never turned off
+
+        let flushed = player.reset();
+        assert_eq!(flushed, vec![vec![0x80, 60, 0]]);
+
+        // Resetting again with nothing held is a no-op.
+        assert!(player.reset().is_empty());
+    }
+
+    #[test]
+    fn test_stop_flushes_held_note() {
+        let mut player = SequencePlayer::new();
+        player.load(Sequence {
+            entries: vec![SequenceEntry {
+                loop_data: make_held_note_loop("held", 60),
+                repeat_count: 10,
+            }],
+        });
+        player.start();
+
+        player.tick(0); // Note 60 on, never turned off
+
+        let flushed = player.stop();
+        assert_eq!(flushed, vec![vec![0x80, 60, 0]]);
+        assert!(!player.playing);
+    }
+
+    // ============ Offline Rendering Tests ============
+
+    #[test]
+    fn test_render_single_entry_one_pass() {
+        let sequence = Sequence {
+            entries: vec![SequenceEntry {
+                loop_data: make_test_loop_named("loop1", 60),
+                repeat_count: 1,
+            }],
+        };
+
+        // make_test_loop_named is a 96-clock (1-bar) loop, so one bar is
+        // exactly one pass.
+        let events: Vec<(u64, Vec<u8>)> = sequence.render(1).collect();
+        assert_eq!(events, vec![(0, vec![0x90, 60, 100]), (48, vec![0x80, 60, 0])]);
+    }
+
+    #[test]
+    fn test_render_honors_repeat_count() {
+        let sequence = Sequence {
+            entries: vec![SequenceEntry {
+                loop_data: make_test_loop_named("loop1", 60),
+                repeat_count: 2,
+            }],
+        };
+
+        let events: Vec<(u64, Vec<u8>)> = sequence.render(2).collect();
+        assert_eq!(
+            events,
+            vec![
+                (0, vec![0x90, 60, 100]),
+                (48, vec![0x80, 60, 0]),
+                (96, vec![0x90, 60, 100]),
+                (144, vec![0x80, 60, 0]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_cycles_back_to_first_entry() {
+        let sequence = Sequence {
+            entries: vec![
+                SequenceEntry {
+                    loop_data: make_test_loop_named("loop1", 60),
+                    repeat_count: 1,
+                },
+                SequenceEntry {
+                    loop_data: make_test_loop_named("loop2", 64),
+                    repeat_count: 1,
+                },
+            ],
+        };
+
+        // Three bars' worth: loop1, loop2, then back around to loop1.
+        let events: Vec<(u64, Vec<u8>)> = sequence.render(3).collect();
+        assert_eq!(
+            events,
+            vec![
+                (0, vec![0x90, 60, 100]),
+                (48, vec![0x80, 60, 0]),
+                (96, vec![0x90, 64, 100]),
+                (144, vec![0x80, 64, 0]),
+                (192, vec![0x90, 60, 100]),
+                (240, vec![0x80, 60, 0]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_stops_at_total_bars() {
+        let sequence = Sequence {
+            entries: vec![SequenceEntry {
+                loop_data: make_test_loop_named("loop1", 60),
+                repeat_count: 10,
+            }],
+        };
+
+        // One bar of a 10-repeat entry: only the first pass's note-on and
+        // note-off fall inside the window.
+        let events: Vec<(u64, Vec<u8>)> = sequence.render(1).collect();
+        assert_eq!(events, vec![(0, vec![0x90, 60, 100]), (48, vec![0x80, 60, 0])]);
+    }
+
+    #[test]
+    fn test_write_smf_produces_valid_header_and_track() {
+        let sequence = Sequence {
+            entries: vec![SequenceEntry {
+                loop_data: make_test_loop_named("loop1", 60),
+                repeat_count: 1,
+            }],
+        };
+
+        let mut buffer = Vec::new();
+        write_smf(&mut buffer, sequence.render(1), 480).unwrap();
+
+        // Header chunk: "MThd", length 6, format 0, 1 track, division 480.
+        assert_eq!(&buffer[0..4], b"MThd");
+        assert_eq!(&buffer[4..8], &6u32.to_be_bytes());
+        assert_eq!(&buffer[8..10], &0u16.to_be_bytes());
+        assert_eq!(&buffer[10..12], &1u16.to_be_bytes());
+        assert_eq!(&buffer[12..14], &480u16.to_be_bytes());
+
+        // Track chunk header.
+        assert_eq!(&buffer[14..18], b"MTrk");
+        let track_len = u32::from_be_bytes(buffer[18..22].try_into().unwrap()) as usize;
+        let track = &buffer[22..22 + track_len];
+
+        // Note-on at tick 0: delta 0x00, then the 3 message bytes.
+        assert_eq!(&track[0..4], &[0x00, 0x90, 60, 100]);
+
+        // The track ends with an end-of-track meta event.
+        assert_eq!(&track[track.len() - 3..], &[0xFF, 0x2F, 0x00]);
+        assert_eq!(track.len(), track_len);
+    }
+
+    #[test]
+    fn test_write_smf_rescales_ticks_per_quarter() {
+        let sequence = Sequence {
+            entries: vec![SequenceEntry {
+                loop_data: make_test_loop_named("loop1", 60),
+                repeat_count: 1,
+            }],
+        };
+
+        // make_test_loop_named's note-off sits at clock 48 (24 ppqn), i.e.
+        // 2 quarter notes in; at a 480 ticks-per-quarter resolution that's
+        // tick 960.
+        let mut buffer = Vec::new();
+        write_smf(&mut buffer, sequence.render(1), 480).unwrap();
+
+        let track_len = u32::from_be_bytes(buffer[18..22].try_into().unwrap()) as usize;
+        let track = &buffer[22..22 + track_len];
+
+        // First event: delta 0, note-on (4 bytes). Second event's delta-time
+        // VLQ starts right after it.
+        let delta_960 = {
+            let mut out = Vec::new();
+            write_vlq(&mut out, 960);
+            out
+        };
+        assert_eq!(&track[4..4 + delta_960.len()], delta_960.as_slice());
+    }
+
+    // ============ Queued Sequence Tests ============
+
+    #[test]
+    fn test_queue_immediate_swaps_on_next_tick() {
+        let mut player = SequencePlayer::new();
+        player.load(Sequence {
+            entries: vec![SequenceEntry {
+                loop_data: make_test_loop_named("loop1", 60),
+                repeat_count: 10,
+            }],
+        });
+        player.start();
+        player.tick(0);
+
+        assert!(!player.queued_sequence_pending());
+        player.queue(
+            Sequence {
+                entries: vec![SequenceEntry {
+                    loop_data: make_test_loop_named("loop2", 64),
+                    repeat_count: 1,
+                }],
+            },
+            QueueQuantum::Immediate,
+        );
+        assert!(player.queued_sequence_pending());
+
+        // Still mid-way through loop1's note, held since tick(0): the swap
+        // should flush it before loop2's own note-on fires.
+        let events = player.tick(10);
+        assert_eq!(events[0], vec![0x80, 60, 0]);
+        assert_eq!(events[1], vec![0x90, 64, 100]);
+        assert_eq!(player.current_loop_name(), Some("loop2"));
+        assert!(!player.queued_sequence_pending());
+    }
+
+    #[test]
+    fn test_queue_next_entry_waits_for_repeat_count() {
+        let mut player = SequencePlayer::new();
+        player.load(Sequence {
+            entries: vec![
+                SequenceEntry {
+                    loop_data: make_test_loop_named("loop1", 60),
+                    repeat_count: 1,
+                },
+                SequenceEntry {
+                    loop_data: make_test_loop_named("loop2", 64),
+                    repeat_count: 1,
+                },
+            ],
+        });
+        player.start();
+        player.tick(0);
+
+        player.queue(
+            Sequence {
+                entries: vec![SequenceEntry {
+                    loop_data: make_test_loop_named("loop3", 67),
+                    repeat_count: 1,
+                }],
+            },
+            QueueQuantum::NextEntry,
+        );
+
+        // loop1's own repeat count isn't exhausted until clock 96; the
+        // queued swap shouldn't preempt it.
+        player.tick(50);
+        assert_eq!(player.current_loop_name(), Some("loop1"));
+        assert!(player.queued_sequence_pending());
+
+        // At the boundary, the queued sequence swaps in instead of
+        // advancing to loop2.
+        let events = player.tick(96);
+        assert_eq!(events[0], vec![0x90, 67, 100]);
+        assert_eq!(player.current_loop_name(), Some("loop3"));
+        assert!(!player.queued_sequence_pending());
+    }
+
+    #[test]
+    fn test_queue_next_bar_waits_for_downbeat() {
+        let mut player = SequencePlayer::new();
+        player.load(Sequence {
+            entries: vec![SequenceEntry {
+                loop_data: make_test_loop_named("loop1", 60),
+                repeat_count: 10,
+            }],
+        });
+        player.start();
+        player.tick(0);
+
+        player.queue(
+            Sequence {
+                entries: vec![SequenceEntry {
+                    loop_data: make_test_loop_named("loop2", 64),
+                    repeat_count: 1,
+                }],
+            },
+            QueueQuantum::NextBar,
+        );
+
+        // Not yet a bar boundary (1 bar = 96 clocks at the default 4/4).
+        player.tick(10);
+        assert_eq!(player.current_loop_name(), Some("loop1"));
+
+        let events = player.tick(96);
+        assert_eq!(events[0], vec![0x80, 60, 0]);
+        assert_eq!(events[1], vec![0x90, 64, 100]);
+        assert_eq!(player.current_loop_name(), Some("loop2"));
+    }
+
+    // ============ Weighted NEXT Transition Tests ============
+
+    #[test]
+    fn test_single_next_target_is_deterministic() {
+        let mut grid = make_grid_with_two_slots();
+        grid.set_next(SlotId('A'), Some(SlotId('B')));
+        // Weight shouldn't matter when there's only one target: it always
+        // wins the draw regardless of what it's set to.
+        grid.bump_next_weight(SlotId('A'), -1000);
+
+        let mut player = SequencePlayer::new();
+        player.load_grid(grid);
+        assert_eq!(player.next_slot_id(), Some(SlotId('B')));
+
+        player.playing = true;
+        player.advance_to_next_slot(0);
+        assert_eq!(player.current_slot_id(), Some(SlotId('B')));
+    }
+
+    #[test]
+    fn test_empty_next_targets_stops_playback() {
+        let grid = make_grid_with_two_slots();
+        // Slot A has no NEXT target configured at all.
+        let mut player = SequencePlayer::new();
+        player.load_grid(grid);
+        assert_eq!(player.next_slot_id(), None);
+
+        player.playing = true;
+        player.advance_to_next_slot(0);
+        assert_eq!(player.current_slot_id(), None);
+    }
+
+    #[test]
+    fn test_zero_weight_target_never_wins_against_positive_weight() {
+        let mut grid = make_grid_with_two_slots();
+        grid.set_next_targets(SlotId('A'), vec![(SlotId('A'), 0), (SlotId('B'), 100)]);
+
+        let mut player = SequencePlayer::new();
+        player.load_grid(grid);
+        // Rolled eagerly on entry to A; with B's weight the only nonzero
+        // share of the total, it must win regardless of the PRNG draw.
+        assert_eq!(player.next_slot_id(), Some(SlotId('B')));
+    }
+
+    #[test]
+    fn test_weighted_draw_is_deterministic_for_fixed_seed() {
+        // `SequencePlayer::new()` always seeds `rng_state` the same way, so
+        // a multi-target weighted draw is reproducible: assert against
+        // whichever target the fixed seed's first roll actually lands on
+        // rather than hard-coding a value that would be a coincidence.
+        let targets = vec![(SlotId('A'), 50), (SlotId('B'), 50)];
+        let mut grid = make_grid_with_two_slots();
+        grid.set_next_targets(SlotId('A'), targets.clone());
+
+        let mut first = SequencePlayer::new();
+        first.load_grid(grid.clone());
+        let rolled = first.next_slot_id();
+        assert!(rolled == Some(SlotId('A')) || rolled == Some(SlotId('B')));
+
+        // Same seed, same targets: the roll is reproducible.
+        let mut second = SequencePlayer::new();
+        second.load_grid(grid);
+        assert_eq!(second.next_slot_id(), rolled);
+    }
 }
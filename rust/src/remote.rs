@@ -0,0 +1,147 @@
+//! Configurable MIDI remote-control mapping layer.
+//!
+//! Generalizes the old hard-coded "CC 119 triggers a screenshot" footswitch
+//! binding into a config-driven table of MIDI CCs to `RemoteAction`s, so a
+//! hardware controller (or the `screenshot` utility binary) can drive any of
+//! the sequence table's keyboard-equivalent actions instead of one fixed
+//! shortcut. Mirrors `PadGridConfig`'s note-on-to-`SlotId` mapping, but for
+//! CC-driven table edits rather than pad launches.
+
+use std::collections::BTreeMap;
+
+use crate::playback::SlotId;
+
+/// A remote-control gesture a mapped MIDI CC can trigger. Named after the
+/// keyboard-equivalent table actions they mirror (see `ui::sequence_table`'s
+/// `select_prev`/`select_next`/`cycle_loop_index`/`cycle_next_slot`), so a
+/// footswitch or hardware controller can drive the same edits the keyboard
+/// does without touching the mouse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteAction {
+    TriggerScreenshot,
+    SelectPrevSlot,
+    SelectNextSlot,
+    CycleLoopPrev,
+    CycleLoopNext,
+    BumpQuanDown,
+    BumpQuanUp,
+    CycleNextPrev,
+    CycleNextNext,
+    StartTransport,
+    StopTransport,
+    /// Jump straight to a slot, rather than stepping prev/next from whatever
+    /// is currently selected. Driven by `note_mappings` rather than
+    /// `cc_mappings`, since a pad/key-per-slot layout maps naturally onto
+    /// Note-On velocity-layer controllers.
+    SelectSlot(SlotId),
+    /// Arm/disarm real-time input capture (see `SequencePlayer::record_event`),
+    /// so a footswitch can start/stop recording without touching the mouse.
+    ToggleRecordArmed,
+    /// Arm/disarm the tick-scheduled arpeggiator (see
+    /// `SequencePlayer::arp_note_on`/`arp_note_off`).
+    ToggleArpArmed,
+    /// Queue a fresh legacy-mode arrangement built from every loop in
+    /// data/out/ to swap in at the next bar (see
+    /// `SequencePlayer::queue`/`QueueQuantum`).
+    QueueAllLoops,
+}
+
+/// First MIDI note of the `SelectSlot` note range (`note_mappings`'s default
+/// covers `NOTE_RANGE_BASE..=NOTE_RANGE_BASE + 25`, one note per slot A-Z).
+/// Deliberately placed just above the pad range that starts at
+/// `PadGridConfig::base_note`'s default (36, Ableton Push's clip-grid origin,
+/// so pads 36..=61) rather than starting at the same note:
+/// both configs map the same 26 `SlotId`s independently, and the pad-hit
+/// path in `midi::handle_incoming_midi_message` is only ever reached for a
+/// note the remote-control path didn't already claim, so an overlapping
+/// default would make the pad path silently dead unless a caller happened
+/// to reconfigure one map to differ from the other.
+const NOTE_RANGE_BASE: u8 = 64;
+
+/// Config-driven CC/Note-to-`RemoteAction` mapping. `cc_for_action` offers
+/// the reverse lookup (which CC number drives a given action); the
+/// `screenshot` utility binary keeps its own copy of the CC table, since it
+/// has no lib to share code with the main app.
+#[derive(Debug, Clone)]
+pub struct RemoteControlConfig {
+    /// MIDI channel the mapped CCs/notes are sent/received on (0-15).
+    pub channel: u8,
+    /// CC number -> action it triggers.
+    pub cc_mappings: BTreeMap<u8, RemoteAction>,
+    /// Note number -> action it triggers on Note-On. Separate from
+    /// `cc_mappings` since a control surface typically dedicates a CC range
+    /// to faders/transport and a note range to pads, and the two shouldn't
+    /// collide.
+    pub note_mappings: BTreeMap<u8, RemoteAction>,
+}
+
+impl Default for RemoteControlConfig {
+    fn default() -> Self {
+        let mut cc_mappings = BTreeMap::new();
+        cc_mappings.insert(119, RemoteAction::TriggerScreenshot);
+        cc_mappings.insert(20, RemoteAction::SelectPrevSlot);
+        cc_mappings.insert(21, RemoteAction::SelectNextSlot);
+        cc_mappings.insert(22, RemoteAction::CycleLoopPrev);
+        cc_mappings.insert(23, RemoteAction::CycleLoopNext);
+        cc_mappings.insert(24, RemoteAction::BumpQuanDown);
+        cc_mappings.insert(25, RemoteAction::BumpQuanUp);
+        cc_mappings.insert(26, RemoteAction::CycleNextPrev);
+        cc_mappings.insert(27, RemoteAction::CycleNextNext);
+        cc_mappings.insert(28, RemoteAction::StartTransport);
+        cc_mappings.insert(29, RemoteAction::StopTransport);
+        cc_mappings.insert(30, RemoteAction::ToggleRecordArmed);
+        cc_mappings.insert(31, RemoteAction::ToggleArpArmed);
+        cc_mappings.insert(32, RemoteAction::QueueAllLoops);
+
+        let mut note_mappings = BTreeMap::new();
+        for (i, slot_id) in SlotId::ALL.iter().enumerate() {
+            note_mappings.insert(NOTE_RANGE_BASE + i as u8, RemoteAction::SelectSlot(*slot_id));
+        }
+
+        Self {
+            channel: 0,
+            cc_mappings,
+            note_mappings,
+        }
+    }
+}
+
+impl RemoteControlConfig {
+    /// Translate an incoming Control Change into the action it maps to, if
+    /// any. Only a full-value (127) press fires, matching a footswitch's
+    /// "down" gesture rather than every CC tick from a fader/knob sweep.
+    pub fn action_for_cc(&self, controller: u8, value: u8) -> Option<RemoteAction> {
+        if value != 127 {
+            return None;
+        }
+        self.cc_mappings.get(&controller).copied()
+    }
+
+    /// Translate an incoming Note-On into the action it maps to, if any.
+    /// Velocity 0 is a Note-Off in disguise (per the MIDI spec convention
+    /// used elsewhere in this crate, e.g. `MidiOut::track_note_state`) and
+    /// never fires an action.
+    pub fn action_for_note(&self, note: u8, velocity: u8) -> Option<RemoteAction> {
+        if velocity == 0 {
+            return None;
+        }
+        self.note_mappings.get(&note).copied()
+    }
+
+    /// Reverse lookup: the CC number that fires `action`, if mapped, so a
+    /// sender can build an outgoing message from this same table.
+    pub fn cc_for_action(&self, action: RemoteAction) -> Option<u8> {
+        self.cc_mappings
+            .iter()
+            .find(|(_, mapped)| **mapped == action)
+            .map(|(cc, _)| *cc)
+    }
+
+    /// Build the raw CC message bytes (status, controller, value) that fires
+    /// `action` on this config's channel, for a sender to write directly to
+    /// a MIDI output connection.
+    pub fn trigger_message(&self, action: RemoteAction) -> Option<[u8; 3]> {
+        let cc = self.cc_for_action(action)?;
+        Some([0xB0 | (self.channel & 0x0F), cc, 127])
+    }
+}
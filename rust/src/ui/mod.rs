@@ -0,0 +1,8 @@
+//! UI widgets for the looper's iced frontend.
+
+mod sequence_table;
+
+pub use sequence_table::{
+    cycle_column_page, cycle_loop_index, cycle_next_slot, scroll_offset_for_slot, select_next,
+    select_prev, table_scroll_id, view_sequence_table, QuanEditState,
+};
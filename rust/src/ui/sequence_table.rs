@@ -3,19 +3,65 @@
 //! Renders a scrollable table of sequence slots (A-Z) with columns for
 //! loop name, length, repeat count, and next slot.
 
-use iced::widget::{button, column, container, pick_list, row, scrollable, text, Column};
+use iced::widget::{
+    button, column, container, pick_list, row, scrollable, text, text_input, Column, Row,
+};
 use iced::{Background, Border, Color, Element, Length, Theme};
 use std::fmt;
+use std::path::PathBuf;
 
-use crate::playback::{PlaybackState, SequenceGrid, SlotId};
+use crate::playback::{LaunchQuantum, PlaybackState, SequenceGrid, SlotId};
 
 /// Column widths for consistent table layout.
 const COL_ID_WIDTH: f32 = 40.0;
 const COL_LOOP_WIDTH: f32 = 250.0;
 const COL_LEN_WIDTH: f32 = 50.0;
 const COL_QUAN_WIDTH: f32 = 60.0;
+const COL_QTZ_WIDTH: f32 = 70.0;
 const COL_NEXT_WIDTH: f32 = 80.0;
+const COL_TRANSPOSE_WIDTH: f32 = 90.0;
+const COL_GAIN_WIDTH: f32 = 90.0;
+const COL_PGM_WIDTH: f32 = 90.0;
+const COL_TRACK_WIDTH: f32 = 300.0;
 const ROW_HEIGHT: f32 = 36.0;
+/// Number of steps shown/editable in the TRACK column's step strip. A
+/// slot's track can technically hold more steps than this, but the table
+/// row only has room to expose a fixed strip -- matches `Track::new`'s
+/// default length used by `Message::ToggleSlotTrack`.
+const TRACK_STEPS_SHOWN: usize = 8;
+/// Number of horizontal column pages. Page 0 is today's LOOP/LEN/QUAN/QTZ/
+/// NEXT columns; page 1 is the per-slot performance parameters (transpose,
+/// gain, program change); page 2 is the step-based TRACK editor. The ID
+/// column is pinned and shown on every page.
+pub const NUM_COLUMN_PAGES: usize = 3;
+/// Height of the scrollable viewport wrapping the table (~8 rows).
+const SCROLLABLE_HEIGHT: f32 = 340.0;
+
+/// `iced` widget id for the table's `scrollable`, so a caller can target it
+/// with `scrollable::snap_to`/`scroll_to` from outside this module (see
+/// `scroll_offset_for_slot`).
+pub fn table_scroll_id() -> scrollable::Id {
+    scrollable::Id::new("sequence-table")
+}
+
+/// Absolute scroll offset that keeps `slot_id`'s row centered in the
+/// table's viewport. Computed from the slot's index rather than relying on
+/// `iced`'s own scroll-into-view, so it keeps working the same whether the
+/// active row advanced one step or jumped (e.g. after a scene launch).
+pub fn scroll_offset_for_slot(slot_id: SlotId) -> scrollable::AbsoluteOffset {
+    let row_stride = ROW_HEIGHT + 2.0; // row height plus the rows column's inter-row spacing
+    let row_top = slot_id.index() as f32 * row_stride;
+    let y = (row_top - SCROLLABLE_HEIGHT / 2.0 + ROW_HEIGHT / 2.0).max(0.0);
+    scrollable::AbsoluteOffset { x: 0.0, y }
+}
+
+/// Which slot's QUAN field (if any) is being edited inline, and the
+/// in-progress text value, so the table can show a text input in place of
+/// the static repeat count for that one row.
+pub struct QuanEditState<'a> {
+    pub editing_slot: Option<SlotId>,
+    pub input_value: &'a str,
+}
 
 /// Wrapper for next slot options in pick_list.
 /// Represents either "None" (stop) or a specific slot ID.
@@ -58,7 +104,7 @@ impl fmt::Display for LoopOption {
 
 impl LoopOption {
     /// Build options list from available loops
-    pub fn from_available(available: &[(String, Option<std::path::PathBuf>)]) -> Vec<LoopOption> {
+    pub fn from_available(available: &[(String, PathBuf)]) -> Vec<LoopOption> {
         let mut opts = vec![LoopOption {
             index: None,
             name: "--".to_string(),
@@ -89,9 +135,86 @@ impl LoopOption {
     }
 }
 
-/// Row highlighting colors.
-fn row_background(is_playing: bool, is_next: bool) -> Color {
-    if is_playing {
+/// Move `current` to the previous slot (A wraps to Z), or the first slot if
+/// nothing was selected yet. Mirrors `TableState::previous` from the
+/// tui-rs stateful table pattern, so arrow-key navigation can drive the
+/// table without the mouse.
+pub fn select_prev(current: Option<SlotId>) -> SlotId {
+    let idx = current.map(|id| id.index()).unwrap_or(0);
+    let prev = if idx == 0 { SlotId::ALL.len() - 1 } else { idx - 1 };
+    SlotId::ALL[prev]
+}
+
+/// Move `current` to the next slot (Z wraps to A), or the first slot if
+/// nothing was selected yet. Mirrors `TableState::next`.
+pub fn select_next(current: Option<SlotId>) -> SlotId {
+    let idx = current.map(|id| id.index()).unwrap_or(0);
+    let next = (idx + 1) % SlotId::ALL.len();
+    SlotId::ALL[next]
+}
+
+/// Cycle the selected slot's loaded loop forward (`delta = 1`) or backward
+/// (`delta = -1`) through `available_loops`, so the LOOP column can be
+/// edited without opening its pick_list. "No loop" is one more position in
+/// the cycle (past the last loop) rather than a dead end.
+pub fn cycle_loop_index(current: Option<usize>, available_len: usize, delta: i32) -> Option<usize> {
+    if available_len == 0 {
+        return None;
+    }
+    let total = available_len as i32 + 1; // +1 for "no loop"
+    let current_pos = current.map(|i| i as i32).unwrap_or(available_len as i32);
+    let next_pos = (current_pos + delta).rem_euclid(total);
+    if next_pos == available_len as i32 {
+        None
+    } else {
+        Some(next_pos as usize)
+    }
+}
+
+/// Cycle a slot's NEXT pointer forward/backward through `--, A, B, ... Z`,
+/// so the NEXT column can be edited without opening its pick_list.
+pub fn cycle_next_slot(current: Option<SlotId>, delta: i32) -> Option<SlotId> {
+    let current_pos = current.map(|id| 1 + id.index() as i32).unwrap_or(0);
+    let total = SlotId::ALL.len() as i32 + 1; // +1 for "--" (stop)
+    let next_pos = (current_pos + delta).rem_euclid(total);
+    if next_pos == 0 {
+        None
+    } else {
+        Some(SlotId::ALL[(next_pos - 1) as usize])
+    }
+}
+
+/// Move the table's active column page forward (`delta = 1`) or backward
+/// (`delta = -1`), wrapping around `NUM_COLUMN_PAGES`. Mirrors
+/// `cycle_loop_index`/`cycle_next_slot`'s wrap-around pattern for the
+/// header's left/right page controls.
+pub fn cycle_column_page(current: usize, delta: i32) -> usize {
+    let pages = NUM_COLUMN_PAGES as i32;
+    (current as i32 + delta).rem_euclid(pages) as usize
+}
+
+/// Border drawn around the currently selected row, distinct from playing/
+/// next/queued background highlighting so keyboard focus is visible
+/// regardless of a row's other state.
+fn focus_border(is_selected: bool) -> Border {
+    if is_selected {
+        Border::default()
+            .rounded(2)
+            .width(2.0)
+            .color(Color::from_rgb(0.9, 0.9, 1.0))
+    } else {
+        Border::default().rounded(2)
+    }
+}
+
+/// Row highlighting colors. A slot queued for launch (`is_queued`) blinks
+/// between its normal background and a bright amber, driven by `blink_on`
+/// toggling on a timer in the caller, so an armed clip is visibly distinct
+/// from one already playing or merely NEXT-linked.
+fn row_background(is_playing: bool, is_next: bool, is_queued: bool, blink_on: bool) -> Color {
+    if is_queued && blink_on {
+        Color::from_rgb(0.95, 0.8, 0.1) // Amber flash
+    } else if is_playing {
         Color::from_rgb(0.15, 0.45, 0.15) // Green
     } else if is_next {
         Color::from_rgb(0.5, 0.35, 0.1) // Orange
@@ -110,16 +233,24 @@ fn cell_color() -> Color {
     Color::from_rgb(0.9, 0.9, 0.9)
 }
 
-/// Render the table header row.
-fn view_table_header<'a, M: 'a>() -> Element<'a, M> {
+/// Render the table header row, plus a left/right page control that pages
+/// the trailing columns between "today's" LOOP/LEN/QUAN/QTZ/NEXT set, the
+/// per-slot performance parameters (TRANSPOSE/GAIN/PGM), and the step-based
+/// TRACK editor, so the window doesn't have to widen to fit them all. The ID column is pinned and always
+/// shown first.
+fn view_table_header<'a, M: 'a + Clone>(
+    column_page: usize,
+    on_page_change: impl Fn(i32) -> M + 'a,
+) -> Element<'a, M> {
     let hdr_color = header_color();
 
-    container(
+    let id_header = container(text("ID").size(12).color(hdr_color))
+        .width(Length::Fixed(COL_ID_WIDTH))
+        .padding([4, 8])
+        .center_y(Length::Fixed(ROW_HEIGHT));
+
+    let page_headers: Element<'a, M> = if column_page == 0 {
         row![
-            container(text("ID").size(12).color(hdr_color))
-                .width(Length::Fixed(COL_ID_WIDTH))
-                .padding([4, 8])
-                .center_y(Length::Fixed(ROW_HEIGHT)),
             container(text("LOOP").size(12).color(hdr_color))
                 .width(Length::Fixed(COL_LOOP_WIDTH))
                 .padding([4, 8])
@@ -132,12 +263,61 @@ fn view_table_header<'a, M: 'a>() -> Element<'a, M> {
                 .width(Length::Fixed(COL_QUAN_WIDTH))
                 .padding([4, 8])
                 .center_y(Length::Fixed(ROW_HEIGHT)),
+            container(text("QTZ").size(12).color(hdr_color))
+                .width(Length::Fixed(COL_QTZ_WIDTH))
+                .padding([4, 8])
+                .center_y(Length::Fixed(ROW_HEIGHT)),
             container(text("NEXT").size(12).color(hdr_color))
                 .width(Length::Fixed(COL_NEXT_WIDTH))
                 .padding([4, 8])
                 .center_y(Length::Fixed(ROW_HEIGHT)),
         ]
-        .spacing(2),
+        .spacing(2)
+        .into()
+    } else if column_page == 1 {
+        row![
+            container(text("TRANSPOSE").size(12).color(hdr_color))
+                .width(Length::Fixed(COL_TRANSPOSE_WIDTH))
+                .padding([4, 8])
+                .center_y(Length::Fixed(ROW_HEIGHT)),
+            container(text("GAIN").size(12).color(hdr_color))
+                .width(Length::Fixed(COL_GAIN_WIDTH))
+                .padding([4, 8])
+                .center_y(Length::Fixed(ROW_HEIGHT)),
+            container(text("PGM").size(12).color(hdr_color))
+                .width(Length::Fixed(COL_PGM_WIDTH))
+                .padding([4, 8])
+                .center_y(Length::Fixed(ROW_HEIGHT)),
+        ]
+        .spacing(2)
+        .into()
+    } else {
+        row![
+            container(text("TRACK").size(12).color(hdr_color))
+                .width(Length::Fixed(COL_TRACK_WIDTH))
+                .padding([4, 8])
+                .center_y(Length::Fixed(ROW_HEIGHT)),
+        ]
+        .spacing(2)
+        .into()
+    };
+
+    let page_controls = row![
+        button(text("<").size(12))
+            .on_press(on_page_change(-1))
+            .padding([2, 6]),
+        text(format!("{}/{}", column_page + 1, NUM_COLUMN_PAGES)).size(12).color(hdr_color),
+        button(text(">").size(12))
+            .on_press(on_page_change(1))
+            .padding([2, 6]),
+    ]
+    .spacing(4)
+    .align_y(iced::Alignment::Center);
+
+    container(
+        row![id_header, page_headers, page_controls]
+            .spacing(2)
+            .align_y(iced::Alignment::Center),
     )
     .style(|_theme: &Theme| container::Style {
         background: Some(Background::Color(Color::from_rgb(0.08, 0.08, 0.08))),
@@ -149,21 +329,43 @@ fn view_table_header<'a, M: 'a>() -> Element<'a, M> {
 
 
 /// Render a single table row for a slot.
+#[allow(clippy::too_many_arguments)]
 fn view_slot_row<'a, M: 'a + Clone>(
     slot_id: SlotId,
+    column_page: usize,
     loop_name: String,
     length_bars: String,
     repeat_count: u32,
+    launch_quantum: LaunchQuantum,
     next_slot: Option<SlotId>,
+    next_weight: u8,
+    transpose: i8,
+    gain_db: f32,
+    program_change: Option<u8>,
+    has_track: bool,
+    track_steps: Vec<bool>,
     is_playing: bool,
     is_next: bool,
+    is_queued: bool,
+    is_selected: bool,
+    blink_on: bool,
     loop_options: Vec<LoopOption>,
+    quan_edit: &QuanEditState<'_>,
     on_loop_change: impl Fn(SlotId, LoopOption) -> M + 'a,
     on_next_change: impl Fn(SlotId, NextSlotOption) -> M + 'a,
-    on_quan_decrement: M,
-    on_quan_increment: M,
+    on_next_weight_change: impl Fn(SlotId, i32) -> M + 'a,
+    on_quantum_change: impl Fn(SlotId, LaunchQuantum) -> M + 'a,
+    on_transpose_change: impl Fn(SlotId, i32) -> M + 'a,
+    on_gain_change: impl Fn(SlotId, f32) -> M + 'a,
+    on_program_change: impl Fn(SlotId, i32) -> M + 'a,
+    on_toggle_track: impl Fn(SlotId) -> M + 'a,
+    on_toggle_step: impl Fn(SlotId, usize) -> M + 'a,
+    on_request_slot: M,
+    on_start_edit_quan: M,
+    on_edit_quan_value: impl Fn(String) -> M + 'a,
+    on_commit_quan_edit: M,
 ) -> Element<'a, M> {
-    let bg_color = row_background(is_playing, is_next);
+    let bg_color = row_background(is_playing, is_next, is_queued, blink_on);
     let txt_color = cell_color();
 
     // Create pick_list for LOOP column
@@ -183,42 +385,130 @@ fn view_slot_row<'a, M: 'a + Clone>(
     .text_size(12)
     .width(Length::Fixed(COL_NEXT_WIDTH - 8.0));
 
-    // QUAN cell: - [count] + buttons
-    let minus_btn = button(text("-").size(12).color(txt_color))
-        .on_press(on_quan_decrement)
-        .padding([1, 4])
-        .style(move |_theme, _status| button::Style {
-            background: Some(Background::Color(Color::from_rgb(0.25, 0.25, 0.25))),
-            text_color: txt_color,
-            border: Border::default().rounded(2),
-            ..Default::default()
-        });
+    // "P" (probability weight) sub-cell: a QUAN-style +/- pair that edits
+    // the primary NEXT target's weight, for branching multiple targets
+    // unevenly. A lone target ignores its weight (it always wins), so this
+    // only matters once more targets are added programmatically.
+    let next_weight_cell = row![
+        button(text("-").size(10))
+            .on_press(on_next_weight_change(slot_id, -5))
+            .padding([0, 4]),
+        text(format!("P:{}", next_weight)).size(10).color(txt_color),
+        button(text("+").size(10))
+            .on_press(on_next_weight_change(slot_id, 5))
+            .padding([0, 4]),
+    ]
+    .spacing(2);
+
+    // Create pick_list for QTZ (launch quantum) column
+    let quantum_picker = pick_list(
+        LaunchQuantum::ALL.to_vec(),
+        Some(launch_quantum),
+        move |q| on_quantum_change(slot_id, q),
+    )
+    .text_size(11)
+    .width(Length::Fixed(COL_QTZ_WIDTH - 8.0));
 
-    let plus_btn = button(text("+").size(12).color(txt_color))
-        .on_press(on_quan_increment)
-        .padding([1, 4])
+    // QUAN cell: click the count to edit it inline; while editing, show a
+    // text input wired to the in-progress value instead.
+    let quan_cell: Element<'a, M> = if quan_edit.editing_slot == Some(slot_id) {
+        text_input("", quan_edit.input_value)
+            .size(12)
+            .width(Length::Fixed(COL_QUAN_WIDTH - 8.0))
+            .on_input(on_edit_quan_value)
+            .on_submit(on_commit_quan_edit)
+            .into()
+    } else {
+        button(text(format!("{}", repeat_count)).size(12).color(txt_color))
+            .on_press(on_start_edit_quan)
+            .padding([1, 4])
+            .style(move |_theme, _status| button::Style {
+                background: Some(Background::Color(Color::from_rgb(0.25, 0.25, 0.25))),
+                text_color: txt_color,
+                border: Border::default().rounded(2),
+                ..Default::default()
+            })
+            .into()
+    };
+
+    // ID cell doubles as the scene-launch trigger: clicking it queues the
+    // slot to take over on its next launch-quantum boundary.
+    let id_button = button(text(slot_id.to_string()).size(14).color(txt_color))
+        .on_press(on_request_slot)
+        .padding([4, 8])
         .style(move |_theme, _status| button::Style {
-            background: Some(Background::Color(Color::from_rgb(0.25, 0.25, 0.25))),
+            background: None,
             text_color: txt_color,
-            border: Border::default().rounded(2),
+            border: Border::default(),
             ..Default::default()
         });
 
-    let quan_cell: Element<'a, M> = row![
-        minus_btn,
-        text(format!("{}", repeat_count)).size(12).color(txt_color),
-        plus_btn,
+    // Performance-parameter cells (page 1): transpose/gain/program-change,
+    // each a QUAN-style +/- pair, matching `next_weight_cell`'s layout.
+    let transpose_cell = row![
+        button(text("-").size(10))
+            .on_press(on_transpose_change(slot_id, -1))
+            .padding([0, 4]),
+        text(format!("{:+}", transpose)).size(12).color(txt_color),
+        button(text("+").size(10))
+            .on_press(on_transpose_change(slot_id, 1))
+            .padding([0, 4]),
     ]
-    .spacing(2)
-    .align_y(iced::Center)
-    .into();
+    .spacing(2);
 
-    container(
+    let gain_cell = row![
+        button(text("-").size(10))
+            .on_press(on_gain_change(slot_id, -0.5))
+            .padding([0, 4]),
+        text(format!("{:+.1}dB", gain_db)).size(12).color(txt_color),
+        button(text("+").size(10))
+            .on_press(on_gain_change(slot_id, 0.5))
+            .padding([0, 4]),
+    ]
+    .spacing(2);
+
+    let program_label = match program_change {
+        Some(pgm) => format!("{}", pgm),
+        None => "--".to_string(),
+    };
+    let program_cell = row![
+        button(text("-").size(10))
+            .on_press(on_program_change(slot_id, -1))
+            .padding([0, 4]),
+        text(program_label).size(12).color(txt_color),
+        button(text("+").size(10))
+            .on_press(on_program_change(slot_id, 1))
+            .padding([0, 4]),
+    ]
+    .spacing(2);
+
+    // TRACK cell (page 2): a toggle that creates/clears a step `Track` for
+    // this slot, plus a strip of per-step toggle buttons that edit
+    // `Track::steps` directly through `on_toggle_step` -- this is the "grid
+    // UI can edit steps directly" entry point alongside file-backed loops.
+    let track_toggle_label = if has_track { "Track: ON" } else { "Track: --" };
+    let track_toggle = button(text(track_toggle_label).size(11))
+        .on_press(on_toggle_track(slot_id))
+        .padding([2, 6]);
+
+    let step_buttons = Row::with_children(
+        (0..TRACK_STEPS_SHOWN)
+            .map(|i| {
+                let filled = track_steps.get(i).copied().unwrap_or(false);
+                let label = if filled { "#" } else { "." };
+                button(text(label).size(11))
+                    .on_press(on_toggle_step(slot_id, i))
+                    .padding([1, 3])
+                    .into()
+            })
+            .collect::<Vec<Element<'a, M>>>(),
+    )
+    .spacing(1);
+
+    let track_cell = row![track_toggle, step_buttons].spacing(6).align_y(iced::Alignment::Center);
+
+    let page_cells: Element<'a, M> = if column_page == 0 {
         row![
-            container(text(slot_id.to_string()).size(14).color(txt_color))
-                .width(Length::Fixed(COL_ID_WIDTH))
-                .padding([4, 8])
-                .center_y(Length::Fixed(ROW_HEIGHT)),
             container(loop_picker)
                 .width(Length::Fixed(COL_LOOP_WIDTH))
                 .padding([2, 4])
@@ -231,16 +521,55 @@ fn view_slot_row<'a, M: 'a + Clone>(
                 .width(Length::Fixed(COL_QUAN_WIDTH))
                 .padding([2, 4])
                 .center_y(Length::Fixed(ROW_HEIGHT)),
-            container(next_picker)
+            container(quantum_picker)
+                .width(Length::Fixed(COL_QTZ_WIDTH))
+                .padding([2, 4])
+                .center_y(Length::Fixed(ROW_HEIGHT)),
+            container(column![next_picker, next_weight_cell].spacing(2))
                 .width(Length::Fixed(COL_NEXT_WIDTH))
                 .padding([2, 4])
                 .center_y(Length::Fixed(ROW_HEIGHT)),
         ]
+        .spacing(2)
+        .into()
+    } else if column_page == 1 {
+        row![
+            container(transpose_cell)
+                .width(Length::Fixed(COL_TRANSPOSE_WIDTH))
+                .padding([2, 4])
+                .center_y(Length::Fixed(ROW_HEIGHT)),
+            container(gain_cell)
+                .width(Length::Fixed(COL_GAIN_WIDTH))
+                .padding([2, 4])
+                .center_y(Length::Fixed(ROW_HEIGHT)),
+            container(program_cell)
+                .width(Length::Fixed(COL_PGM_WIDTH))
+                .padding([2, 4])
+                .center_y(Length::Fixed(ROW_HEIGHT)),
+        ]
+        .spacing(2)
+        .into()
+    } else {
+        row![container(track_cell)
+            .width(Length::Fixed(COL_TRACK_WIDTH))
+            .padding([2, 4])
+            .center_y(Length::Fixed(ROW_HEIGHT))]
+        .spacing(2)
+        .into()
+    };
+
+    container(
+        row![
+            container(id_button)
+                .width(Length::Fixed(COL_ID_WIDTH))
+                .center_y(Length::Fixed(ROW_HEIGHT)),
+            page_cells,
+        ]
         .spacing(2),
     )
     .style(move |_theme: &Theme| container::Style {
         background: Some(Background::Color(bg_color)),
-        border: Border::default().rounded(2),
+        border: focus_border(is_selected),
         ..Default::default()
     })
     .height(Length::Fixed(ROW_HEIGHT))
@@ -249,25 +578,63 @@ fn view_slot_row<'a, M: 'a + Clone>(
 
 /// Build the complete scrollable sequence table.
 ///
-/// Returns an Element that displays all 26 slots with highlighting for
-/// the currently playing slot and the next slot.
+/// Returns an Element that displays all 26 slots with highlighting for the
+/// currently playing slot, the NEXT-linked slot, and (blinking) a slot
+/// queued via `pending_launch` to take over on its next launch-quantum
+/// boundary.
 ///
 /// Callbacks:
 /// - `on_loop_change`: invoked when user changes a slot's loop
-/// - `on_next_change`: invoked when user changes a slot's NEXT pointer
-/// - `on_quan_decrement`: invoked when user clicks - to decrease repeat count
-/// - `on_quan_increment`: invoked when user clicks + to increase repeat count
+/// - `on_next_change`: invoked when user changes a slot's primary NEXT target
+/// - `on_next_weight_change`: invoked when user adjusts the primary NEXT
+///   target's weight via the "P" sub-cell
+/// - `on_quantum_change`: invoked when user changes a slot's launch quantum
+/// - `on_transpose_change`/`on_gain_change`/`on_program_change`: invoked from
+///   the column_page == 1 performance-parameter cells
+/// - `on_toggle_track`/`on_toggle_step`: invoked from the column_page == 2
+///   TRACK cell's track on/off toggle and per-step toggle buttons
+/// - `on_page_change`: invoked when user clicks a header page-arrow, with
+///   `-1`/`1` to move `column_page` back/forward (see `cycle_column_page`)
+/// - `on_request_slot`: invoked when user clicks a slot's ID to queue it for launch
+/// - `on_start_edit_quan`: invoked when user clicks the QUAN cell to edit it
+/// - `on_edit_quan_value`: invoked as the user types in the QUAN edit box
+/// - `on_commit_quan_edit`: invoked when the user submits the QUAN edit
+///
+/// `selected_slot` is the row currently under keyboard focus (see
+/// `select_prev`/`select_next`); it's rendered with a distinct border so a
+/// user driving the table from the keyboard -- navigating rows and running
+/// edit actions against `selected_slot` without ever clicking a pick_list --
+/// can see where they are.
+///
+/// `column_page` selects which trailing column set is shown (see
+/// `NUM_COLUMN_PAGES`); the ID column is pinned and always shown first.
+#[allow(clippy::too_many_arguments)]
 pub fn view_sequence_table<'a, M: 'a + Clone>(
     grid: &SequenceGrid,
     playback_state: Option<PlaybackState>,
-    available_loops: &[(String, Option<std::path::PathBuf>)],
+    pending_launch: Option<SlotId>,
+    selected_slot: Option<SlotId>,
+    column_page: usize,
+    blink_on: bool,
+    available_loops: &[(String, PathBuf)],
+    quan_edit: QuanEditState<'a>,
     on_loop_change: impl Fn(SlotId, Option<usize>) -> M + 'a + Copy,
     on_next_change: impl Fn(SlotId, Option<SlotId>) -> M + 'a + Copy,
-    on_quan_decrement: impl Fn(SlotId) -> M + 'a + Copy,
-    on_quan_increment: impl Fn(SlotId) -> M + 'a + Copy,
+    on_next_weight_change: impl Fn(SlotId, i32) -> M + 'a + Copy,
+    on_quantum_change: impl Fn(SlotId, LaunchQuantum) -> M + 'a + Copy,
+    on_transpose_change: impl Fn(SlotId, i32) -> M + 'a + Copy,
+    on_gain_change: impl Fn(SlotId, f32) -> M + 'a + Copy,
+    on_program_change: impl Fn(SlotId, i32) -> M + 'a + Copy,
+    on_toggle_track: impl Fn(SlotId) -> M + 'a + Copy,
+    on_toggle_step: impl Fn(SlotId, usize) -> M + 'a + Copy,
+    on_page_change: impl Fn(i32) -> M + 'a + Copy,
+    on_request_slot: impl Fn(SlotId) -> M + 'a + Copy,
+    on_start_edit_quan: impl Fn(SlotId) -> M + 'a + Copy,
+    on_edit_quan_value: impl Fn(String) -> M + 'a + Copy,
+    on_commit_quan_edit: M,
 ) -> Element<'a, M> {
     let current_slot = playback_state.map(|s| s.current_slot);
-    let next_slot = playback_state.and_then(|s| grid.get(s.current_slot).next_slot);
+    let next_slot = playback_state.and_then(|s| s.next_slot);
 
     // Build loop options once
     let loop_options = LoopOption::from_available(available_loops);
@@ -279,30 +646,61 @@ pub fn view_sequence_table<'a, M: 'a + Clone>(
         .map(|slot| {
             let is_playing = current_slot == Some(slot.id);
             let is_next = next_slot == Some(slot.id);
+            let is_queued = pending_launch == Some(slot.id);
+            let is_selected = selected_slot == Some(slot.id);
 
             view_slot_row(
                 slot.id,
+                column_page,
                 slot.loop_name().to_string(),
                 slot.length_bars(),
                 slot.repeat_count,
-                slot.next_slot,
+                slot.launch_quantum,
+                slot.primary_next_target(),
+                slot.primary_next_weight(),
+                slot.transpose,
+                slot.gain_db,
+                slot.program_change,
+                slot.has_track(),
+                slot.track_data
+                    .as_ref()
+                    .map(|t| t.steps.iter().map(|s| s.is_some()).collect())
+                    .unwrap_or_default(),
                 is_playing,
                 is_next,
+                is_queued,
+                is_selected,
+                blink_on,
                 loop_options.clone(),
+                &quan_edit,
                 move |slot_id, opt| on_loop_change(slot_id, opt.index),
                 move |slot_id, opt| on_next_change(slot_id, opt.0),
-                on_quan_decrement(slot.id),
-                on_quan_increment(slot.id),
+                on_next_weight_change,
+                on_quantum_change,
+                on_transpose_change,
+                on_gain_change,
+                on_program_change,
+                on_toggle_track,
+                on_toggle_step,
+                on_request_slot(slot.id),
+                on_start_edit_quan(slot.id),
+                on_edit_quan_value,
+                on_commit_quan_edit.clone(),
             )
         })
         .collect();
 
-    let table_content = column![view_table_header(), Column::with_children(rows).spacing(2),]
-        .spacing(4)
-        .padding(8);
+    let table_content = column![
+        view_table_header(column_page, on_page_change),
+        Column::with_children(rows).spacing(2),
+    ]
+    .spacing(4)
+    .padding(8);
 
-    // Wrap in scrollable - show ~8 rows at a time
+    // Wrap in scrollable - show ~8 rows at a time. Given an `id`, so a
+    // caller can scroll it programmatically (see `scroll_offset_for_slot`).
     scrollable(table_content)
-        .height(Length::Fixed(340.0))
+        .id(table_scroll_id())
+        .height(Length::Fixed(SCROLLABLE_HEIGHT))
         .into()
 }